@@ -0,0 +1,171 @@
+use alloc::vec::Vec;
+
+use ckb_cinnabar_verifier::{
+    calc_blake2b_hash,
+    re_exports::ckb_std::{self},
+    Result, Verification,
+};
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{
+        packed::Script,
+        prelude::{Entity, Unpack},
+    },
+    debug,
+    high_level::{load_cell_lock, load_cell_lock_hash, load_header, load_script, load_script_hash},
+};
+use common::{
+    card::{merge_cards_dna, roll_card_dna, CardPool},
+    contract::SporeData,
+    hardcoded::MERGE_CARD_COUNT,
+};
+use database::CARD_POOL;
+
+use crate::{Context, ScriptError, ScriptType};
+
+// Spores burned by the dob global must have been committed under the dob commitment lock paired
+// with it, whose args are the flag, the dob global lock hash and the receiver lock hash, so that
+// the revealed spores can only go to the receiver
+fn check_commitment(
+    inputs: &[(usize, Script, SporeData)],
+    outputs: &[(usize, Script, SporeData)],
+) -> Result<()> {
+    let dob_global_lock = load_script()?;
+    let commitment_lock = load_cell_lock(inputs[0].0, Source::Input)?;
+    let args: Vec<u8> = commitment_lock.args().unpack();
+    if commitment_lock.code_hash().as_slice() != dob_global_lock.code_hash().as_slice()
+        || commitment_lock.hash_type() != dob_global_lock.hash_type()
+        || args.len() != 65
+        || args[0] != u8::from(ScriptType::DobCommitment)
+        || args[1..33] != load_script_hash()?
+    {
+        return Err(ScriptError::DobSporeNotCommitted.into());
+    }
+    for (index, _, _) in inputs {
+        let lock = load_cell_lock(*index, Source::Input)?;
+        if lock.as_slice() != commitment_lock.as_slice() {
+            return Err(ScriptError::DobSporeNotCommitted.into());
+        }
+    }
+    for (index, _, _) in outputs {
+        if load_cell_lock_hash(*index, Source::Output)?[..] != args[33..] {
+            return Err(ScriptError::DobReceiverMismatch.into());
+        }
+    }
+    Ok(())
+}
+
+// Seed is derived from `material` and the headers of the blocks which committed the spores, those
+// are unknown until the commitment is on chain, so the result cannot be picked in advance
+fn derive_seed(material: &[u8], inputs: &[(usize, Script, SporeData)]) -> Result<u64> {
+    let mut seed_material = material.to_vec();
+    for (index, _, _) in inputs {
+        let header = load_header(*index, Source::Input).map_err(|_| ScriptError::HeaderNotSet)?;
+        let nonce: u128 = header.nonce().unpack();
+        seed_material.extend_from_slice(&(nonce as u64).to_le_bytes());
+    }
+    let seed_hash = calc_blake2b_hash(&[&seed_material]);
+    Ok(u64::from_le_bytes(seed_hash[..8].try_into().unwrap()))
+}
+
+#[derive(Default)]
+pub struct SporeCardsMerge {}
+
+impl Verification<Context> for SporeCardsMerge {
+    fn verify(&mut self, name: &str, ctx: &mut Context) -> Result<Option<&str>> {
+        debug!("process: {}", name);
+
+        let Some(dob_global) = ctx.dob_global.as_ref() else {
+            return Err(ScriptError::BadDobGlobalMode.into());
+        };
+        let dob_global_data = serde_molecule::to_vec(dob_global, false)
+            .map_err(|_| ScriptError::BrokenDobGlobalMolecule)?;
+        if dob_global_data != ctx.dob_global_output_data {
+            return Err(ScriptError::DobGlobalUnexpectedChanged.into());
+        }
+
+        // Merging burns exactly MERGE_CARD_COUNT card spores and mints a brand new one
+        let input_cards = dob_global.search_card_dobs(Source::Input)?;
+        let output_cards = dob_global.search_card_dobs(Source::Output)?;
+        if input_cards.len() != MERGE_CARD_COUNT || output_cards.len() != 1 {
+            return Err(ScriptError::CardMergeCountMismatch.into());
+        }
+        let (_, output_type, output_spore) = &output_cards[0];
+        if input_cards
+            .iter()
+            .any(|(_, type_, _)| type_.args().as_slice() == output_type.args().as_slice())
+        {
+            return Err(ScriptError::CardMergeCountMismatch.into());
+        }
+
+        check_commitment(&input_cards, &output_cards)?;
+
+        // Upgraded DNA is derived from the burned DNAs and the committing block headers
+        let dna_set = input_cards
+            .iter()
+            .map(|(_, _, spore)| spore.dna().ok_or(ScriptError::GameplaySporeDnaUnexpected))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        let seed = derive_seed(&dna_set.concat(), &input_cards)?;
+        let expected_dna =
+            merge_cards_dna(&dna_set, seed).ok_or(ScriptError::CardMergeNotAllowed)?;
+        if output_spore.dna() != Some(expected_dna) {
+            return Err(ScriptError::CardMergeDnaMismatch.into());
+        }
+
+        Ok(None)
+    }
+}
+
+#[derive(Default)]
+pub struct BlindboxUnbox {}
+
+impl Verification<Context> for BlindboxUnbox {
+    fn verify(&mut self, name: &str, ctx: &mut Context) -> Result<Option<&str>> {
+        debug!("process: {}", name);
+
+        let Some(dob_global) = ctx.dob_global.as_mut() else {
+            return Err(ScriptError::BadDobGlobalMode.into());
+        };
+
+        // Unboxing burns one blindbox spore and mints one card spore, the input blindboxes are
+        // already searched by the root verifier
+        let input_blindboxes = &ctx.input_blindboxes;
+        let output_blindboxes = dob_global.search_blindbox_dobs(Source::Output)?;
+        let input_cards = dob_global.search_card_dobs(Source::Input)?;
+        let output_cards = dob_global.search_card_dobs(Source::Output)?;
+        if input_blindboxes.len() != 1
+            || !output_blindboxes.is_empty()
+            || !input_cards.is_empty()
+            || output_cards.len() != 1
+        {
+            return Err(ScriptError::UnboxCountMismatch.into());
+        }
+        check_commitment(input_blindboxes, &output_cards)?;
+
+        // Price grows along with the global unboxed count
+        if dob_global.get_ckb_payment() < dob_global.unbox_price() {
+            return Err(ScriptError::UnboxPaymentNotEnough.into());
+        }
+
+        // Card DNA is derived from the burned blindbox and the committing block header
+        let (_, blindbox_type, _) = &input_blindboxes[0];
+        let seed = derive_seed(blindbox_type.args().as_slice(), input_blindboxes)?;
+        let card_pool: CardPool = serde_molecule::from_slice(CARD_POOL, false)
+            .map_err(|_| ScriptError::BrokenCardPoolMolecule)?;
+        let expected_dna = roll_card_dna(&card_pool, seed).ok_or(ScriptError::UnboxCardNotFound)?;
+        let (_, _, output_spore) = &output_cards[0];
+        if output_spore.dna() != Some(expected_dna) {
+            return Err(ScriptError::UnboxDnaMismatch.into());
+        }
+
+        // Only the global unboxed count is allowed to change
+        dob_global.global_unboxed_count += 1;
+        let expected_data = serde_molecule::to_vec(dob_global, false)
+            .map_err(|_| ScriptError::BrokenDobGlobalMolecule)?;
+        if expected_data != ctx.dob_global_output_data {
+            return Err(ScriptError::DobGlobalUnexpectedChanged.into());
+        }
+
+        Ok(None)
+    }
+}
@@ -1,7 +1,9 @@
+mod dob;
 mod session;
 mod spore;
 mod token;
 
+pub use dob::*;
 pub use session::*;
 pub use spore::*;
 pub use token::*;
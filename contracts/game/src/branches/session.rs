@@ -1,4 +1,4 @@
-use alloc::{vec, vec::Vec};
+use alloc::vec::Vec;
 
 use ckb_cinnabar_verifier::{
     calc_blake2b_hash,
@@ -8,21 +8,25 @@ use ckb_cinnabar_verifier::{
 use ckb_std::{
     ckb_constants::Source, ckb_types::prelude::Unpack, debug, high_level::load_witness_args,
 };
-use common::{
-    contract::PveSessionMaterials,
-    operation::{GameOperation, GameOperationSet},
-};
+use common::{contract::PveSessionMaterials, operation::GameOperationSet};
 use game_core::PveSystemRuntime;
 
-use crate::{Context, ScriptError};
+use crate::{count_locked_cells, game_script, Context, ScriptError, ScriptType};
 
 #[derive(Default)]
 pub struct AnalyzeIteration {}
 
 impl Verification<Context> for AnalyzeIteration {
-    fn verify(&mut self, name: &str, _: &mut Context) -> Result<Option<&str>> {
+    fn verify(&mut self, name: &str, ctx: &mut Context) -> Result<Option<&str>> {
         debug!("process: {}", name);
 
+        // A brand new pve session paired with the iterated game global means session creation
+        let session_lock = game_script(ScriptType::PveSession, &ctx.global_type_hash)?;
+        if count_locked_cells(&session_lock, Source::Input) == 0
+            && count_locked_cells(&session_lock, Source::Output) > 0
+        {
+            return Ok(Some("PveSessionCreate"));
+        }
         Ok(None)
     }
 }
@@ -103,48 +107,17 @@ impl PveUpdate {
         //     serde_molecule::from_slice(&materials.archive_input, false)
         //         .map_err(|_| ScriptError::BrokenPveSessionMaterialsMolecule)?
         // };
-        let mut game =
-            PveSystemRuntime::new(ctx.game_seed).map_err(|error| Error::Custom(error.into()))?;
 
         // Operation replay
-        for operation in operation_set.operations {
-            match operation {
-                GameOperation::StartGame => {
-                    let mut cards = vec![];
-                    cards.append(&mut ctx.gameplay_cards.clone());
-                    game.start_game(
-                        pve_session_data.player_level,
-                        pve_session_data.action_point,
-                        cards,
-                    )
-                    .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::StartBattle(enemy_level) => {
-                    game.start_battle(enemy_level, pve_session_data.version)
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::RoundOver => {
-                    game.round_over()
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::SpellCard(card_runtime_id, target_runtime_id) => {
-                    game.spell_card(card_runtime_id, target_runtime_id)
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::SelectCard(card_runtime_id_set) => {
-                    game.select_card(card_runtime_id_set)
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::HealHp => {
-                    game.heal_hp()
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-                GameOperation::DestroyCard => {
-                    game.destroy_card()
-                        .map_err(|error| Error::Custom(error.into()))?;
-                }
-            }
-        }
+        let game = PveSystemRuntime::replay(
+            ctx.game_seed,
+            pve_session_data.player_level,
+            pve_session_data.action_point,
+            ctx.gameplay_cards.clone(),
+            pve_session_data.version,
+            &operation_set.operations,
+        )
+        .map_err(|error| Error::Custom(error.into()))?;
 
         Ok((game, materials))
     }
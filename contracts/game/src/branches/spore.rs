@@ -1,12 +1,79 @@
+use alloc::vec::Vec;
+
 use ckb_cinnabar_verifier::{
     re_exports::ckb_std::{self},
     Result, Verification,
 };
-use ckb_std::debug;
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{packed::Script, prelude::Entity},
+    debug,
+    high_level::{load_cell_lock, load_cell_type, load_input_since, QueryIter},
+};
+use common::hardcoded::{is_spore_code_hash, ABOUT_3_DAYS_BLOCKS};
+
+use crate::{
+    count_locked_cells, game_global_update, game_script, Context, ScriptError, ScriptType,
+};
+
+// Since of relative block number, which is the only since that counts the lockup period
+const SINCE_RELATIVE_FLAG: u64 = 1 << 63;
+const SINCE_METRIC_MASK: u64 = 0b11 << 61;
+const SINCE_VALUE_MASK: u64 = (1 << 56) - 1;
+
+fn lockup_indices(ctx: &Context, source: Source) -> Result<Vec<usize>> {
+    let lockup_lock = game_script(ScriptType::SporeLockup, &ctx.global_type_hash)?;
+    Ok(QueryIter::new(load_cell_lock, source)
+        .enumerate()
+        .filter_map(|(i, lock)| (lock.as_slice() == lockup_lock.as_slice()).then_some(i))
+        .collect())
+}
 
-use crate::Context;
+fn spore_type(index: usize, source: Source) -> Result<Option<Script>> {
+    let type_ = load_cell_type(index, source)?
+        .filter(|type_| is_spore_code_hash(&type_.code_hash().raw_data()));
+    Ok(type_)
+}
 
-fn check_spore_cards(_ctx: &mut Context, _check_lockup: bool) -> Result<()> {
+// Spores are locked up under the spore lockup lock paired with the game global
+fn check_lockup(ctx: &Context) -> Result<()> {
+    for index in lockup_indices(ctx, Source::Output)? {
+        if spore_type(index, Source::Output)?.is_none() {
+            return Err(ScriptError::BadSporeLockupMode.into());
+        }
+    }
+    Ok(())
+}
+
+// Locked spores are redeemed by the game global owner, either along with the settlement of the
+// paired pve session, or once they have been locked for the lockup period
+fn check_redeem(ctx: &Context) -> Result<()> {
+    if !game_global_update(&ctx.global_type_hash) {
+        return Err(ScriptError::BadRedeemMode.into());
+    }
+    let session_lock = game_script(ScriptType::PveSession, &ctx.global_type_hash)?;
+    let settled = count_locked_cells(&session_lock, Source::Input) > 0;
+    let output_types = QueryIter::new(load_cell_type, Source::Output)
+        .flatten()
+        .collect::<Vec<_>>();
+    for index in lockup_indices(ctx, Source::Input)? {
+        let spore_type =
+            spore_type(index, Source::Input)?.ok_or(ScriptError::RedeemSporeTypeNotFound)?;
+        if output_types
+            .iter()
+            .all(|type_| type_.as_slice() != spore_type.as_slice())
+        {
+            return Err(ScriptError::SporeCannotBeBurned.into());
+        }
+        if !settled {
+            let since = load_input_since(index, Source::Input)?;
+            let relative_blocks =
+                since & SINCE_RELATIVE_FLAG != 0 && since & SINCE_METRIC_MASK == 0;
+            if !relative_blocks || since & SINCE_VALUE_MASK < ABOUT_3_DAYS_BLOCKS {
+                return Err(ScriptError::RedeemPeriodNotEnough.into());
+            }
+        }
+    }
     Ok(())
 }
 
@@ -17,7 +84,7 @@ impl Verification<Context> for SporeCardsRedeemChecker {
     fn verify(&mut self, name: &str, ctx: &mut Context) -> Result<Option<&str>> {
         debug!("process: {}", name);
 
-        check_spore_cards(ctx, false)?;
+        check_redeem(ctx)?;
         Ok(None)
     }
 }
@@ -29,7 +96,7 @@ impl Verification<Context> for SporeCardsLockupChecker {
     fn verify(&mut self, name: &str, ctx: &mut Context) -> Result<Option<&str>> {
         debug!("process: {}", name);
 
-        check_spore_cards(ctx, true)?;
+        check_lockup(ctx)?;
         Ok(None)
     }
 }
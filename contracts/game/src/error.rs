@@ -22,6 +22,7 @@ define_errors!(
         BadRedeemMode,
         BadPvpSettlementMode,
         BadSporeLockupMode,
+        BadDobCommitmentMode,
 
         BrokenGameGlobalMolecule,
         BrokenTokenIssueArgs,
@@ -52,5 +53,18 @@ define_errors!(
         MaterialHashMismatch,
         CardsDnaSetMismatchFromCelldep,
         CardsDnaSetMismatchFromDefault,
+        BadDobGlobalMode,
+        BrokenDobGlobalMolecule,
+        DobGlobalUnexpectedChanged,
+        CardMergeCountMismatch,
+        CardMergeNotAllowed,
+        CardMergeDnaMismatch,
+        UnboxCountMismatch,
+        UnboxPaymentNotEnough,
+        UnboxCardNotFound,
+        UnboxDnaMismatch,
+        BrokenCardPoolMolecule,
+        DobSporeNotCommitted,
+        DobReceiverMismatch,
     }
 );
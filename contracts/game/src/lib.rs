@@ -5,4 +5,5 @@ extern crate alloc;
 mod error;
 mod types;
 
+pub use error::ScriptError;
 pub use types::*;
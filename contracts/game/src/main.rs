@@ -1,5 +1,5 @@
-#![no_main]
-#![no_std]
+#![cfg_attr(not(feature = "native-simulator"), no_main)]
+#![cfg_attr(not(feature = "native-simulator"), no_std)]
 
 use alloc::vec::Vec;
 use ckb_cinnabar_verifier::{
@@ -8,11 +8,20 @@ use ckb_cinnabar_verifier::{
 };
 use ckb_std::{
     ckb_constants::Source,
-    ckb_types::prelude::Unpack,
+    ckb_types::{
+        packed::Script,
+        prelude::{Builder, Entity, Pack, Unpack},
+    },
     debug,
-    high_level::{load_cell_data, load_cell_type, load_cell_type_hash, load_header, QueryIter},
+    high_level::{
+        load_cell_data, load_cell_lock, load_cell_lock_hash, load_cell_type, load_cell_type_hash,
+        load_header, load_script, load_script_hash, QueryIter,
+    },
+};
+use common::{
+    contract::{DobGlobalStatistics, SporeData},
+    hardcoded::DNA_LEN,
 };
-use common::hardcoded::DNA_LEN;
 use types::{GameConfig, PveSession, ScriptType};
 
 mod branches;
@@ -27,8 +36,25 @@ struct Context {
     config: GameConfig,
     pve_session_data: Option<PveSession>,
     args_payload: Vec<u8>,
+    global_type_hash: Vec<u8>,
     game_seed: u64,
     gameplay_cards: Vec<[u8; DNA_LEN]>,
+    dob_global: Option<DobGlobalStatistics>,
+    dob_global_output_data: Vec<u8>,
+    input_blindboxes: Vec<(usize, Script, SporeData)>,
+}
+
+/// Game script of `script_type` paired with the game global cell of `global_type_hash`, which
+/// shares the code of the running script
+fn game_script(script_type: ScriptType, global_type_hash: &[u8]) -> Result<Script> {
+    let args = [&[u8::from(script_type)][..], global_type_hash].concat();
+    Ok(load_script()?.as_builder().args(args.pack()).build())
+}
+
+fn count_locked_cells(lock: &Script, source: Source) -> usize {
+    QueryIter::new(load_cell_lock, source)
+        .filter(|cell_lock| cell_lock.as_slice() == lock.as_slice())
+        .count()
 }
 
 fn game_global_update(global_type_hash: &[u8]) -> bool {
@@ -77,7 +103,10 @@ impl Verification<Context> for Root {
                     // Creation pattern
                     (false, true) => Ok("CreateGameGlobalCell".into()),
                     // Transfer pattern
-                    (true, true) => Ok("AnalyzeIteration".into()),
+                    (true, true) => {
+                        ctx.global_type_hash = load_script_hash()?.to_vec();
+                        Ok("AnalyzeIteration".into())
+                    }
                     // Burn pattern
                     (true, false) => Ok(None),
                     _ => unreachable!(),
@@ -117,6 +146,7 @@ impl Verification<Context> for Root {
                     }
                     ("PveSettlement".into(), Source::Input)
                 };
+                ctx.global_type_hash = ctx.args_payload.clone();
 
                 // Pve session cell has unique type script
                 let type_ = load_cell_type(lock_ins[0], Source::Input)?;
@@ -156,6 +186,56 @@ impl Verification<Context> for Root {
                 }
                 Ok("PvpSettlement".into())
             }
+            ScriptType::SporeLockup => {
+                debug!("SporeLockup Mode");
+
+                // Lock of spores only runs when they are spent, which means redemption
+                if ctx.args_payload.len() != 32 {
+                    return Err(ScriptError::ScriptArgsUnexpected.into());
+                }
+                ctx.global_type_hash = ctx.args_payload.clone();
+                Ok("SporeCardsRedeemChecker".into())
+            }
+            ScriptType::DobCommitment => {
+                debug!("DobCommitment Mode");
+
+                // Committed spores are only revealed by the paired dob global, which verifies them
+                if ctx.args_payload.len() != 64 {
+                    return Err(ScriptError::ScriptArgsUnexpected.into());
+                }
+                if !QueryIter::new(load_cell_lock_hash, Source::Input)
+                    .any(|lock_hash| lock_hash[..] == ctx.args_payload[..32])
+                {
+                    return Err(ScriptError::BadDobCommitmentMode.into());
+                }
+                Ok(None)
+            }
+            ScriptType::DobGlobal => {
+                debug!("DobGlobal Mode");
+
+                // Dob global cell works as the lock proxy of card cluster, so it must be iterated
+                if lock_ins.len() != 1 || lock_outs.len() != 1 {
+                    return Err(ScriptError::BadDobGlobalMode.into());
+                }
+
+                // Load dob global data
+                let input_data = load_cell_data(lock_ins[0], Source::Input)?;
+                let dob_global: DobGlobalStatistics =
+                    serde_molecule::from_slice(&input_data, false)
+                        .map_err(|_| ScriptError::BrokenDobGlobalMolecule)?;
+                ctx.dob_global_output_data = load_cell_data(lock_outs[0], Source::Output)?;
+
+                // Burning blindbox means unboxing, otherwise merging cards
+                ctx.input_blindboxes = dob_global.search_blindbox_dobs(Source::Input)?;
+                let next = if ctx.input_blindboxes.is_empty() {
+                    "SporeCardsMerge"
+                } else {
+                    "BlindboxUnbox"
+                };
+                ctx.dob_global = Some(dob_global);
+
+                Ok(next.into())
+            }
         }
     }
 }
@@ -186,4 +266,10 @@ cinnabar_main!(
     ("PveSessionBurn", PveSessionBurn),
     ("SporeCardsLockupChecker", SporeCardsLockupChecker),
     ("SporeCardsRedeemChecker", SporeCardsRedeemChecker),
+    ("SporeCardsMerge", SporeCardsMerge),
+    ("BlindboxUnbox", BlindboxUnbox),
 );
+
+// Native simulator builds this file as its library, see `native-simulators/game-sim`
+#[cfg(feature = "native-simulator")]
+ckb_std::entry_simulator!(program_entry);
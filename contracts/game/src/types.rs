@@ -1,15 +1,19 @@
 use ckb_cinnabar_verifier::re_exports::ckb_std::ckb_types::packed::Script;
 use common::hardcoded::XUDT_CODE_HASH;
-use serde::{Deserialize, Serialize};
 
 use crate::error::ScriptError;
 
+pub use common::cell::{GameGlobal, PveSession};
+
 #[repr(u8)]
 pub enum ScriptType {
     GameData,
     TokenIssuer,
     PveSession,
     PvpSession,
+    DobGlobal,
+    SporeLockup,
+    DobCommitment,
 }
 
 impl TryFrom<u8> for ScriptType {
@@ -21,6 +25,9 @@ impl TryFrom<u8> for ScriptType {
             1 => Ok(Self::TokenIssuer),
             2 => Ok(Self::PveSession),
             3 => Ok(Self::PvpSession),
+            4 => Ok(Self::DobGlobal),
+            5 => Ok(Self::SporeLockup),
+            6 => Ok(Self::DobCommitment),
             _ => Err(ScriptError::UnknownScriptType),
         }
     }
@@ -32,27 +39,6 @@ impl From<ScriptType> for u8 {
     }
 }
 
-#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
-pub struct GameGlobal {
-    pub action_point: u16,
-    // pve
-    pub pve_count: u16,
-    pub pve_hunted_gold: u32,
-    pub pve_easy_mode_count: u16,
-    pub pve_killed_enemy_count: u16,
-    pub pve_normal_mode_count: u16,
-    pub pve_hard_mode_count: u16,
-    pub pve_casued_damage: u32,
-    pub pve_sufferred_damage: u32,
-    pub pve_blocked_damage: u32,
-    pub pve_healed_hp: u32,
-    // pvp
-    pub pvp_win_count: u16,
-    pub pvp_lose_count: u16,
-    pub pvp_looted_gold: u128,
-    pub pvp_stolen_gold: u128,
-}
-
 pub struct GameConfig {}
 
 impl Default for GameConfig {
@@ -66,11 +52,3 @@ impl GameConfig {
         script.code_hash().raw_data().as_ref() == XUDT_CODE_HASH
     }
 }
-
-#[derive(Serialize, Deserialize)]
-pub struct PveSession {
-    pub version: u8,
-    pub action_point: u16,
-    pub player_level: u8,
-    pub material_hash: [u8; 32],
-}
@@ -2,10 +2,20 @@ use alloc::{
     string::{String, ToString},
     vec::Vec,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_molecule::dynvec_serde;
 
-use crate::{enum_with_display, value::ValueType};
+use crate::{
+    enum_with_display,
+    hardcoded::{
+        CARD_VERSION, COMMON_DROP_RATE, DNA, DNA_CARD_INDEX, DNA_GOLDEN_INDEX, DNA_LEN,
+        DNA_RARITY_INDEX, DNA_SEED_INDEX, DNA_SEED_MASK, DNA_VERSION_INDEX, EPIC_DROP_RATE,
+        EPIC_MAX, GOLDEN_THRESHOLD, LEGENDARY_DROP_RATE, LEGENDARY_MAX, MERGE_CARD_COUNT,
+        RARE_DROP_RATE, RARE_MAX,
+    },
+    pool::VersionIndex,
+    value::ValueType,
+};
 
 enum_with_display!(
     #[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
@@ -40,9 +50,119 @@ enum_with_display!(
     #[cfg_attr(feature = "debug", derive(Debug))]
     pub enum CardName {
         Attack,
+        Ambush,
     }
 );
 
+// Number of `CardName` members, which card identifiers of DNA wrap around
+const CARD_NAME_COUNT: u8 = 2;
+
+impl From<u8> for CardName {
+    fn from(value: u8) -> Self {
+        match value % CARD_NAME_COUNT {
+            0 => Self::Attack,
+            _ => Self::Ambush,
+        }
+    }
+}
+
+enum_with_display!(
+    #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+    #[cfg_attr(feature = "debug", derive(Debug))]
+    pub enum Rarity {
+        Legendary,
+        Epic,
+        Rare,
+        Common,
+    }
+);
+
+impl From<u8> for Rarity {
+    fn from(value: u8) -> Self {
+        if value <= LEGENDARY_MAX {
+            Self::Legendary
+        } else if value <= EPIC_MAX {
+            Self::Epic
+        } else if value <= RARE_MAX {
+            Self::Rare
+        } else {
+            Self::Common
+        }
+    }
+}
+
+impl Rarity {
+    /// Inclusive range of rarity byte that belongs to this tier
+    pub fn range(&self) -> (u8, u8) {
+        match self {
+            Self::Legendary => (0, LEGENDARY_MAX),
+            Self::Epic => (LEGENDARY_MAX + 1, EPIC_MAX),
+            Self::Rare => (EPIC_MAX + 1, RARE_MAX),
+            Self::Common => (RARE_MAX + 1, u8::MAX),
+        }
+    }
+
+    /// Drop rate of this tier in per mille
+    pub fn drop_rate(&self) -> u16 {
+        match self {
+            Self::Legendary => LEGENDARY_DROP_RATE,
+            Self::Epic => EPIC_DROP_RATE,
+            Self::Rare => RARE_DROP_RATE,
+            Self::Common => COMMON_DROP_RATE,
+        }
+    }
+
+    pub fn upgrade(&self) -> Option<Self> {
+        match self {
+            Self::Legendary => None,
+            Self::Epic => Some(Self::Legendary),
+            Self::Rare => Some(Self::Epic),
+            Self::Common => Some(Self::Rare),
+        }
+    }
+
+    pub fn downgrade(&self) -> Option<Self> {
+        match self {
+            Self::Legendary => Some(Self::Epic),
+            Self::Epic => Some(Self::Rare),
+            Self::Rare => Some(Self::Common),
+            Self::Common => None,
+        }
+    }
+
+    /// Roll a tier by drop rates, the rarer tier is checked first
+    pub fn roll(seed: u16) -> Self {
+        Self::roll_among(seed, |_| true).unwrap_or(Self::Common)
+    }
+
+    /// Roll a tier by drop rates among the tiers that `available` accepts, so the drops of
+    /// other tiers are shared by them in proportion to their own rates, rather than all
+    /// falling onto a neighbour tier
+    pub fn roll_among(seed: u16, available: impl Fn(&Self) -> bool) -> Option<Self> {
+        let tiers = [Self::Legendary, Self::Epic, Self::Rare, Self::Common]
+            .into_iter()
+            .filter(|rarity| available(rarity));
+        let total: u16 = tiers.clone().map(|rarity| rarity.drop_rate()).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut point = seed % total;
+        for rarity in tiers {
+            if point < rarity.drop_rate() {
+                return Some(rarity);
+            }
+            point -= rarity.drop_rate();
+        }
+        None
+    }
+
+    /// Roll a rarity byte inside the range of this tier
+    pub fn roll_byte(&self, seed: u64) -> u8 {
+        let (min, max) = self.range();
+        (min as u64 + seed % (max as u64 - min as u64 + 1)) as u8
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub enum AwakeTrigger {
@@ -73,11 +193,87 @@ pub struct CardConfig {
     pub value_1: Option<ValueType>,
 }
 
-#[derive(Serialize, Deserialize)]
+impl CardConfig {
+    pub fn rarity_tier(&self) -> Rarity {
+        Rarity::from(self.rarity)
+    }
+}
+
+#[derive(Serialize)]
 #[serde(transparent)]
 pub struct CardPool {
     #[serde(with = "dynvec_serde")]
-    pub inner: Vec<CardConfig>,
+    inner: Vec<CardConfig>,
+    #[serde(skip)]
+    index: VersionIndex,
+}
+
+impl<'de> Deserialize<'de> for CardPool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(transparent)]
+        struct CardPoolHelper {
+            #[serde(with = "dynvec_serde")]
+            inner: Vec<CardConfig>,
+        }
+        let helper = CardPoolHelper::deserialize(deserializer)?;
+        Ok(CardPool::new(helper.inner))
+    }
+}
+
+impl CardPool {
+    pub fn new(cards: Vec<CardConfig>) -> Self {
+        let index = VersionIndex::new(cards.iter().map(|card| (card.name as u8, card.version)));
+        Self {
+            inner: cards,
+            index,
+        }
+    }
+
+    pub fn cards(&self) -> &[CardConfig] {
+        &self.inner
+    }
+
+    /// Card template of the version, which is the latest one not newer than `version`
+    pub fn card_by_name(&self, name: CardName, version: u8) -> Option<&CardConfig> {
+        self.index
+            .find(name as u8, version)
+            .map(|offset| &self.inner[offset])
+    }
+
+    /// All playable card templates of the version, one template for each card name
+    pub fn versioned_cards(&self, version: u8) -> impl Iterator<Item = &CardConfig> {
+        self.index
+            .resolve(version)
+            .map(|offset| &self.inner[offset])
+    }
+
+    pub fn cards_by_rarity(
+        &self,
+        rarity: Rarity,
+        version: u8,
+    ) -> impl Iterator<Item = &CardConfig> {
+        self.versioned_cards(version)
+            .filter(move |card| card.rarity_tier() == rarity)
+    }
+
+    /// Pick a random card of the rarity tier from the pool of specific version
+    pub fn random_card_by_rarity(
+        &self,
+        rarity: Rarity,
+        version: u8,
+        seed: u64,
+    ) -> Option<&CardConfig> {
+        let count = self.cards_by_rarity(rarity, version).count();
+        if count == 0 {
+            return None;
+        }
+        self.cards_by_rarity(rarity, version)
+            .nth((seed % count as u64) as usize)
+    }
 }
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
@@ -91,7 +287,9 @@ pub struct Awake {
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct Card {
     pub seed: u64,
+    pub version: u8,
     pub name: CardName,
+    pub rarity: u8,
     pub golden: bool,
     pub target: CardTarget,
     pub cost: u8,
@@ -104,6 +302,10 @@ pub struct Card {
 }
 
 impl Card {
+    pub fn rarity_tier(&self) -> Rarity {
+        Rarity::from(self.rarity)
+    }
+
     pub fn descripted(&mut self) {
         self.description = {
             let mut parts = self
@@ -126,11 +328,16 @@ impl Card {
     }
 }
 
+/// Instance card from the template, the seed is masked by `DNA_SEED_MASK` so that the card can
+/// be encoded into DNA without losing its seed
 pub fn instance_card_by_seed(card: &CardConfig, seed: u64) -> Card {
+    let seed = seed & DNA_SEED_MASK;
     let seeds = seed.to_le_bytes();
     let mut card = Card {
         seed,
+        version: card.version,
         name: card.name,
+        rarity: card.rarity,
         golden: false,
         target: card.target,
         cost: card.cost.value_u8(seeds[0]),
@@ -156,8 +363,114 @@ pub fn instance_card_by_seed(card: &CardConfig, seed: u64) -> Card {
     card
 }
 
-pub fn get_card_template_by_name(card_pool_bin: &[u8], name: CardName) -> Option<CardConfig> {
-    let card_pool: CardPool =
-        serde_molecule::from_slice(card_pool_bin, false).expect("card pool bin broken");
-    card_pool.inner.into_iter().find(|card| card.name == name)
+/// Decode card DNA into a playable card, following the DNA layout in `hardcoded`
+///
+/// Card values come from the template of `version`, which is the content version of
+/// game session rather than the version recorded in DNA.
+pub fn decode_dna(card_pool: &CardPool, dna: &DNA, version: u8) -> Option<Card> {
+    let name = CardName::from(dna[DNA_CARD_INDEX]);
+    let template = card_pool.card_by_name(name, version)?;
+    let mut seed = [0u8; 8];
+    seed[..DNA_LEN - DNA_SEED_INDEX].copy_from_slice(&dna[DNA_SEED_INDEX..]);
+    let mut card = instance_card_by_seed(template, u64::from_le_bytes(seed));
+    card.version = dna[DNA_VERSION_INDEX];
+    card.rarity = dna[DNA_RARITY_INDEX];
+    card.golden = dna[DNA_GOLDEN_INDEX] != 0;
+    Some(card)
+}
+
+/// Encode card back into DNA, the reverse operation of `decode_dna`
+pub fn encode_dna(card: &Card) -> DNA {
+    let mut dna = [0u8; DNA_LEN];
+    dna[DNA_VERSION_INDEX] = card.version;
+    dna[DNA_RARITY_INDEX] = card.rarity;
+    dna[DNA_GOLDEN_INDEX] = card.golden as u8;
+    dna[DNA_CARD_INDEX] = card.name as u8;
+    dna[DNA_SEED_INDEX..].copy_from_slice(&card.seed.to_le_bytes()[..DNA_LEN - DNA_SEED_INDEX]);
+    dna
+}
+
+// What a seed is used for while rolling DNA, each purpose derives its own seed so that no byte
+// decides two things at once, e.g. the golden flag and the picked card
+#[repr(u64)]
+#[derive(Clone, Copy)]
+enum RollPurpose {
+    Rarity = 1,
+    Card,
+    Golden,
+    Values,
+}
+
+// Hash `seed` along with `purpose` by the finalizer of splitmix64
+fn purpose_seed(seed: u64, purpose: RollPurpose) -> u64 {
+    let mut z = seed.wrapping_add((purpose as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Seed bytes stored in DNA, which roll the values of card
+fn value_seed_bytes(seed: u64) -> [u8; DNA_LEN - DNA_SEED_INDEX] {
+    let seeds = purpose_seed(seed, RollPurpose::Values).to_le_bytes();
+    seeds[..DNA_LEN - DNA_SEED_INDEX].try_into().unwrap()
+}
+
+/// Merge `MERGE_CARD_COUNT` card DNAs of the same card and rarity tier into one upgraded DNA
+///
+/// The merged card is raised to the next rarity tier, or turned golden if it is already
+/// legendary; `None` is returned if the DNAs cannot be merged. The result only depends on
+/// the input DNAs and `seed`, so both chain and client derive the same DNA.
+pub fn merge_cards_dna(dna_set: &[DNA], seed: u64) -> Option<DNA> {
+    if dna_set.len() != MERGE_CARD_COUNT {
+        return None;
+    }
+    let first = dna_set[0];
+    let rarity = Rarity::from(first[DNA_RARITY_INDEX]);
+    if dna_set.iter().any(|dna| {
+        dna[DNA_CARD_INDEX] != first[DNA_CARD_INDEX]
+            || Rarity::from(dna[DNA_RARITY_INDEX]) != rarity
+    }) {
+        return None;
+    }
+    let golden = dna_set.iter().any(|dna| dna[DNA_GOLDEN_INDEX] != 0);
+    let (rarity, golden) = match rarity.upgrade() {
+        Some(upgraded) => (upgraded, golden),
+        None if !golden => (rarity, true),
+        None => return None,
+    };
+    let mut dna = [0u8; DNA_LEN];
+    dna[DNA_VERSION_INDEX] = dna_set
+        .iter()
+        .map(|dna| dna[DNA_VERSION_INDEX])
+        .max()
+        .unwrap_or_default();
+    dna[DNA_RARITY_INDEX] = rarity.roll_byte(purpose_seed(seed, RollPurpose::Rarity));
+    dna[DNA_GOLDEN_INDEX] = golden as u8;
+    dna[DNA_CARD_INDEX] = first[DNA_CARD_INDEX];
+    dna[DNA_SEED_INDEX..].copy_from_slice(&value_seed_bytes(seed));
+    Some(dna)
+}
+
+/// Roll a brand new card DNA from the card pool, which is what a blindbox turns into
+///
+/// The rarity tier is rolled by drop rates first, then a card of that tier is picked. Only
+/// tiers with cards are rolled, so the drop rates between them hold however the pool is
+/// filled, and `None` is returned if the pool has no card at all.
+pub fn roll_card_dna(card_pool: &CardPool, seed: u64) -> Option<DNA> {
+    let rarity = Rarity::roll_among(purpose_seed(seed, RollPurpose::Rarity) as u16, |rarity| {
+        card_pool
+            .cards_by_rarity(*rarity, CARD_VERSION)
+            .next()
+            .is_some()
+    })?;
+    let card_seed = purpose_seed(seed, RollPurpose::Card);
+    let card = card_pool.random_card_by_rarity(rarity, CARD_VERSION, card_seed)?;
+    let mut dna = [0u8; DNA_LEN];
+    dna[DNA_VERSION_INDEX] = card.version;
+    dna[DNA_RARITY_INDEX] = card.rarity;
+    dna[DNA_GOLDEN_INDEX] =
+        ((purpose_seed(seed, RollPurpose::Golden) as u8) < GOLDEN_THRESHOLD) as u8;
+    dna[DNA_CARD_INDEX] = card.name as u8;
+    dna[DNA_SEED_INDEX..].copy_from_slice(&value_seed_bytes(seed));
+    Some(dna)
 }
@@ -0,0 +1,40 @@
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::hardcoded;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct GameGlobal {
+    pub action_point: u16,
+    // pve
+    pub pve_count: u16,
+    pub pve_hunted_gold: u32,
+    pub pve_easy_mode_count: u16,
+    pub pve_killed_enemy_count: u16,
+    pub pve_normal_mode_count: u16,
+    pub pve_hard_mode_count: u16,
+    pub pve_casued_damage: u32,
+    pub pve_sufferred_damage: u32,
+    pub pve_blocked_damage: u32,
+    pub pve_healed_hp: u32,
+    // pvp
+    pub pvp_win_count: u16,
+    pub pvp_lose_count: u16,
+    pub pvp_looted_gold: u128,
+    pub pvp_stolen_gold: u128,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PveSession {
+    pub version: u8,
+    pub action_point: u16,
+    pub player_level: u8,
+    pub material_hash: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PveSessionMaterials {
+    pub dna_collection: Vec<hardcoded::DNA>,
+    pub archive_input: Vec<u8>,
+    pub archive_output: Vec<u8>,
+}
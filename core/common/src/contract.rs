@@ -9,6 +9,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::hardcoded;
 
+pub use crate::cell::PveSessionMaterials;
+
 #[derive(Serialize, Deserialize)]
 pub struct SporeData {
     pub content_type: Vec<u8>,
@@ -61,11 +63,7 @@ impl DobGlobalStatistics {
             let Some(type_) = type_ else {
                 continue;
             };
-            let code_hash = type_.code_hash().raw_data();
-            if hardcoded::SPORE_CODE_HASH_SET
-                .iter()
-                .all(|hash| hash != code_hash.as_ref())
-            {
+            if !hardcoded::is_spore_code_hash(&type_.code_hash().raw_data()) {
                 continue;
             }
             let data = load_cell_data(i, source)?;
@@ -92,6 +90,17 @@ impl DobGlobalStatistics {
         self.search_spores(source, &self.card_cluster_id)
     }
 
+    /// Price of the next unboxing in shannons, `ckb_base` and `ckb_increase_per_unbox` are
+    /// stored in whole CKB so they are scaled by `CKB_DECIMAL` here
+    pub fn unbox_price(&self) -> u64 {
+        let increase = self
+            .ckb_increase_per_unbox
+            .saturating_mul(self.global_unboxed_count as u64);
+        self.ckb_base
+            .saturating_add(increase)
+            .saturating_mul(hardcoded::CKB_DECIMAL)
+    }
+
     pub fn get_ckb_payment(&self) -> u64 {
         QueryIter::new(load_cell_lock, Source::Output)
             .enumerate()
@@ -106,10 +115,3 @@ impl DobGlobalStatistics {
             .sum::<u64>()
     }
 }
-
-#[derive(Serialize, Deserialize)]
-pub struct PveSessionMaterials {
-    pub dna_collection: Vec<hardcoded::DNA>,
-    pub archive_input: Vec<u8>,
-    pub archive_output: Vec<u8>,
-}
@@ -7,6 +7,8 @@ use crate::{enum_with_display, value::ValueType};
 pub struct EffectConfig {
     pub name: EffectName,
     pub trap: bool,
+    /// Required by traps, which stay face-down until it is met
+    pub trigger: Option<TrapTrigger>,
     pub owner_source: Option<bool>,
     pub owner_target: Option<bool>,
     pub value: Option<ValueType>,
@@ -18,14 +20,40 @@ enum_with_display!(
     #[cfg_attr(feature = "debug", derive(Debug))]
     pub enum EffectName {
         ExtraDamage,
+        Counterattack,
+        Poison,
+        Bleed,
+        Weak,
+        Vulnerable,
+        Strength,
+        Thorns,
+        Regeneration,
+        Stun,
+        EnergyGain,
     }
 );
 
+/// Condition that springs a trap, checked against every signal after it has been run
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum TrapTrigger {
+    /// The owner of trap takes damage from an opponent
+    #[serde(rename = "attacked")]
+    Attacked,
+    /// An opponent of the owner adds an effect to anyone
+    #[serde(rename = "opponent_buffed")]
+    OpponentBuffed,
+    /// Hp of the owner drops below the percent of its max hp
+    #[serde(rename = "hp_below")]
+    HpBelow(u8),
+}
+
 #[derive(Clone, Copy, Serialize, Deserialize)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct Effect {
     pub name: EffectName,
     pub trap: bool,
+    pub trigger: Option<TrapTrigger>,
     pub owner_source: Option<bool>,
     pub owner_target: Option<bool>,
     pub value: Option<u8>,
@@ -37,6 +65,7 @@ pub fn roulette_effect(effect: EffectConfig, seed: u64) -> Effect {
     Effect {
         name: effect.name,
         trap: effect.trap,
+        trigger: effect.trigger,
         owner_source: effect.owner_source,
         owner_target: effect.owner_target,
         value: effect.value.map(|param| param.value_u8(seeds[0])),
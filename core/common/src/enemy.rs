@@ -1,10 +1,11 @@
 use alloc::vec::Vec;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_molecule::dynvec_serde;
 
 use crate::{
-    effect::{Effect, EffectConfig},
+    effect::{roulette_effect, Effect, EffectConfig},
     enum_with_display,
+    pool::VersionIndex,
     value::ValueType,
 };
 
@@ -110,7 +111,7 @@ pub struct EnemyConfig {
 }
 
 enum_with_display!(
-    #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+    #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
     pub enum EnemyName {
         Goblin,
         Orc,
@@ -123,11 +124,81 @@ enum_with_display!(
     }
 );
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 #[serde(transparent)]
 pub struct EnemyPool {
     #[serde(with = "dynvec_serde")]
-    pub inner: Vec<EnemyConfig>,
+    inner: Vec<EnemyConfig>,
+    #[serde(skip)]
+    index: VersionIndex,
+}
+
+impl<'de> Deserialize<'de> for EnemyPool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(transparent)]
+        struct EnemyPoolHelper {
+            #[serde(with = "dynvec_serde")]
+            inner: Vec<EnemyConfig>,
+        }
+        let helper = EnemyPoolHelper::deserialize(deserializer)?;
+        Ok(EnemyPool::new(helper.inner))
+    }
+}
+
+impl EnemyPool {
+    pub fn new(enemies: Vec<EnemyConfig>) -> Self {
+        let index = VersionIndex::new(
+            enemies
+                .iter()
+                .map(|enemy| (enemy.name as u8, enemy.version)),
+        );
+        Self {
+            inner: enemies,
+            index,
+        }
+    }
+
+    pub fn enemies(&self) -> &[EnemyConfig] {
+        &self.inner
+    }
+
+    /// Enemy template of the version, which is the latest one not newer than `version`
+    pub fn enemy_by_name(&self, name: EnemyName, version: u8) -> Option<&EnemyConfig> {
+        self.index
+            .find(name as u8, version)
+            .map(|offset| &self.inner[offset])
+    }
+
+    /// All enemy templates of the level and version, one template for each enemy name
+    pub fn enemies_by_level(
+        &self,
+        level: EnemyLevel,
+        version: u8,
+    ) -> impl Iterator<Item = &EnemyConfig> {
+        self.index
+            .resolve(version)
+            .map(|offset| &self.inner[offset])
+            .filter(move |enemy| enemy.level == level)
+    }
+
+    /// Pick a random enemy template of the level from the pool of specific version
+    pub fn random_enemy_by_level(
+        &self,
+        level: EnemyLevel,
+        version: u8,
+        seed: u64,
+    ) -> Option<&EnemyConfig> {
+        let count = self.enemies_by_level(level, version).count();
+        if count == 0 {
+            return None;
+        }
+        self.enemies_by_level(level, version)
+            .nth((seed % count as u64) as usize)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -169,3 +240,49 @@ pub struct WeightedAction {
     pub use_count: u8,
     pub condition_hit: [u8; Condition::_LENGTH_ as usize],
 }
+
+/// Roll an enemy of the level and version, the template is picked by the whole seed and its
+/// attributes are rolled with the same seed layout as `roulette_player`
+pub fn roulette_enemy(
+    enemy_pool: &EnemyPool,
+    level: EnemyLevel,
+    version: u8,
+    seed: u64,
+) -> Option<Enemy> {
+    let template = enemy_pool.random_enemy_by_level(level, version, seed)?;
+    let seeds = seed.to_le_bytes();
+    Some(Enemy {
+        seed,
+        name: template.name,
+        level: template.level,
+        hp: template.hp.value_u16(seeds[0]),
+        gold: template.gold.value_u16(seeds[1]),
+        attack: template.attack.value_u8(seeds[2]),
+        defense: template.defense.value_u8(seeds[3]),
+        spirit: template.spirit.value_u8(seeds[4]),
+        powerup_threshold: template.powerup_threshold.value_u8(seeds[5]),
+        actions: template.actions.clone(),
+    })
+}
+
+/// Roll an action config of enemy into the action to take, with the same seed layout as
+/// `roulette_effect`
+pub fn roulette_action(action: &ActionConfig, seed: u64) -> Action {
+    let seeds = seed.to_le_bytes();
+    match action {
+        ActionConfig::Attack(damage) => Action::UseAttack(damage.value_u8(seeds[0])),
+        ActionConfig::MultipleAttack(damage, times) => {
+            Action::UseMultipleAttack(damage.value_u8(seeds[0]), times.value_u8(seeds[1]))
+        }
+        ActionConfig::Defense(block) => Action::UseDefense(block.value_u8(seeds[0])),
+        ActionConfig::Shield(shield) => Action::UseShield(shield.value_u8(seeds[0])),
+        ActionConfig::Effect(effect) => Action::UseEffect(roulette_effect(effect.clone(), seed)),
+        ActionConfig::SummonCreature(level, count) => Action::SummonCreature(*level, *count),
+        ActionConfig::AddAttack(attack) => Action::AddEnemyAttack(attack.value_u8(seeds[0])),
+        ActionConfig::AddDefense(defense) => Action::AddEnemyDefense(defense.value_u8(seeds[0])),
+        ActionConfig::AddShield(shield) => Action::AddEnemeyShield(shield.value_u8(seeds[0])),
+        ActionConfig::AddEffect(effect) => {
+            Action::AddEnemeyEffect(roulette_effect(effect.clone(), seed))
+        }
+    }
+}
@@ -18,6 +18,22 @@ pub const SPORE_CODE_HASH_SET: [[u8; 32]; 3] = [
         0x2c, 0x1e,
     ],
 ];
+// Type hash of the type id script with args of 32 0xff bytes, which can never be produced on
+// chain, tests running the native simulator deploy always-success under it to mock spores
+#[cfg(feature = "mocked-spore")]
+pub const MOCKED_SPORE_CODE_HASH: [u8; 32] = [
+    0x56, 0x80, 0x9f, 0xaf, 0xb1, 0x2d, 0xce, 0x1f, 0x4a, 0x1c, 0x21, 0x12, 0x84, 0x44, 0x05, 0x12,
+    0xfb, 0x6b, 0x2a, 0x56, 0x6b, 0xff, 0x75, 0x26, 0x15, 0x15, 0x9b, 0x10, 0xe1, 0xa2, 0xd1, 0x72,
+];
+
+pub fn is_spore_code_hash(code_hash: &[u8]) -> bool {
+    #[cfg(feature = "mocked-spore")]
+    if code_hash == MOCKED_SPORE_CODE_HASH {
+        return true;
+    }
+    SPORE_CODE_HASH_SET.iter().any(|hash| hash == code_hash)
+}
+
 pub const TYPE_BURN_CODE_HASH: [[u8; 32]; 2] = [
     // mainnet/testnet
     [
@@ -42,12 +58,21 @@ pub const ABOUT_3_DAYS_BLOCKS: u64 = 30000;
 pub const DNA_LEN: usize = 10;
 pub const GOLDEN_THRESHOLD: u8 = 15;
 pub const MAX_ACTION_POINT: u16 = 150;
+pub const MAX_SIGNALS_PER_OPERATION: u16 = 1024;
+pub const MAX_TRANSFORM_DEPTH: u8 = 8;
 pub const TOKEN_DECIMAL: u128 = 100_000_000;
 pub const CKB_DECIMAL: u64 = 100_000_000;
 
 pub const CARD_VERSION: u8 = 0;
 pub const ENEMY_VERSION: u8 = 0;
 
+// Layout version of game runtime archives, which must be bumped whenever the archived layout
+// changes, archives of other versions are refused rather than misread
+// 1. cards are archived by DNA instead of seed and name
+// 2. effects carry the `trigger` of traps, which also changes the layout of effect configs in
+//    the enemy pool, so pool bins of `database` have to be regenerated along
+pub const ARCHIVE_VERSION: u8 = 2;
+
 // Initial basic playable cards for every players
 pub const DEFAULT_GAMEPLAY_CARDS: [[u8; DNA_LEN]; 20] = [
     [0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
@@ -74,6 +99,23 @@ pub const DEFAULT_GAMEPLAY_CARDS: [[u8; DNA_LEN]; 20] = [
 
 pub type DNA = [u8; DNA_LEN];
 
+// Card DNA layout, which must be kept the same between chain and clients
+// 1. [0]: version of the card pool that the card is minted from
+// 2. [1]: rarity byte, classified into tiers by `LEGENDARY_MAX`/`EPIC_MAX`/`RARE_MAX`
+// 3. [2]: golden flag, any non-zero value means golden
+// 4. [3]: card identifier, which wraps around the indices of `CardName`, e.g. identifiers of
+//    `DEFAULT_GAMEPLAY_CARDS` decode into `Attack` if even, otherwise `Ambush`
+// 5. [4..10]: seed bytes in little endian, used to roll the values of card
+//
+// Only the lower 6 bytes of card seed fit in DNA, so seeds are masked by `DNA_SEED_MASK` when
+// cards are instanced, which keeps `Card.seed` the same after encoding and decoding
+pub const DNA_VERSION_INDEX: usize = 0;
+pub const DNA_RARITY_INDEX: usize = 1;
+pub const DNA_GOLDEN_INDEX: usize = 2;
+pub const DNA_CARD_INDEX: usize = 3;
+pub const DNA_SEED_INDEX: usize = 4;
+pub const DNA_SEED_MASK: u64 = (1 << (8 * (DNA_LEN - DNA_SEED_INDEX))) - 1;
+
 // Define rarity tier boundaries
 // 1. 0-50: Legendary
 // 2. 51-100: Epic
@@ -82,3 +124,9 @@ pub type DNA = [u8; DNA_LEN];
 pub const LEGENDARY_MAX: u8 = 50;
 pub const EPIC_MAX: u8 = 100;
 pub const RARE_MAX: u8 = 150;
+
+// Drop rates of rarity tiers in per mille, which should be summed up to 1000
+pub const LEGENDARY_DROP_RATE: u16 = 20;
+pub const EPIC_DROP_RATE: u16 = 80;
+pub const RARE_DROP_RATE: u16 = 250;
+pub const COMMON_DROP_RATE: u16 = 650;
@@ -2,11 +2,15 @@
 extern crate alloc;
 
 pub mod hardcoded;
+pub mod pool;
 pub mod value;
 
 #[cfg(feature = "card")]
 pub mod card;
 
+#[cfg(feature = "cell")]
+pub mod cell;
+
 #[cfg(feature = "contract")]
 pub mod contract;
 
@@ -1,8 +1,8 @@
 use alloc::vec::Vec;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_molecule::dynvec_serde;
 
-use crate::value::ValueType;
+use crate::{pool::VersionIndex, value::ValueType};
 
 #[derive(Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -27,11 +27,48 @@ pub struct PlayerConfig {
     pub hard_action_point: u8,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 #[serde(transparent)]
 pub struct PlayerPool {
     #[serde(with = "dynvec_serde")]
-    pub inner: Vec<PlayerConfig>,
+    inner: Vec<PlayerConfig>,
+    #[serde(skip)]
+    index: VersionIndex,
+}
+
+impl<'de> Deserialize<'de> for PlayerPool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(transparent)]
+        struct PlayerPoolHelper {
+            #[serde(with = "dynvec_serde")]
+            inner: Vec<PlayerConfig>,
+        }
+        let helper = PlayerPoolHelper::deserialize(deserializer)?;
+        Ok(PlayerPool::new(helper.inner))
+    }
+}
+
+impl PlayerPool {
+    pub fn new(players: Vec<PlayerConfig>) -> Self {
+        // Player templates are not versioned, so only the level is indexed
+        let index = VersionIndex::new(players.iter().map(|player| (player.level, 0)));
+        Self {
+            inner: players,
+            index,
+        }
+    }
+
+    pub fn players(&self) -> &[PlayerConfig] {
+        &self.inner
+    }
+
+    pub fn player_by_level(&self, level: u8) -> Option<&PlayerConfig> {
+        self.index.find(level, 0).map(|offset| &self.inner[offset])
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -57,12 +94,8 @@ pub struct Player {
     pub hard_action_point: u8,
 }
 
-pub fn roulette_player(player_pool_bin: &[u8], player_level: u8, seed: u64) -> Option<Player> {
-    let player_pool: PlayerPool = serde_molecule::from_slice(player_pool_bin, false).ok()?;
-    let template = player_pool
-        .inner
-        .into_iter()
-        .find(|player| player.level == player_level)?;
+pub fn roulette_player(player_pool: &PlayerPool, player_level: u8, seed: u64) -> Option<Player> {
+    let template = player_pool.player_by_level(player_level)?;
     let seeds = seed.to_le_bytes();
     Some(Player {
         level: template.level,
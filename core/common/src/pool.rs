@@ -0,0 +1,42 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+/// Lookup index of content pool, which groups template offsets by identifier and sorts
+/// them by version, so that lookups don't need to walk through the whole pool
+#[derive(Default)]
+pub struct VersionIndex {
+    inner: BTreeMap<u8, Vec<(u8, usize)>>,
+}
+
+impl VersionIndex {
+    pub fn new(keys: impl Iterator<Item = (u8, u8)>) -> Self {
+        let mut inner: BTreeMap<u8, Vec<(u8, usize)>> = BTreeMap::new();
+        keys.enumerate().for_each(|(offset, (id, version))| {
+            inner.entry(id).or_default().push((version, offset));
+        });
+        inner
+            .values_mut()
+            .for_each(|versions| versions.sort_by_key(|(version, _)| *version));
+        Self { inner }
+    }
+
+    /// Offset of the latest template not newer than `version`
+    pub fn find(&self, id: u8, version: u8) -> Option<usize> {
+        latest_offset(self.inner.get(&id)?, version)
+    }
+
+    /// Offsets of the templates that `version` resolves to, one for each identifier
+    pub fn resolve(&self, version: u8) -> impl Iterator<Item = usize> + '_ {
+        self.inner
+            .values()
+            .filter_map(move |versions| latest_offset(versions, version))
+    }
+}
+
+// Versions are sorted stably, so templates duplicating the latest version keep their pool
+// order and the first of them is taken, as walking through the pool would
+fn latest_offset(versions: &[(u8, usize)], version: u8) -> Option<usize> {
+    let end = versions.partition_point(|(template_version, _)| *template_version <= version);
+    let (latest, _) = versions.get(end.checked_sub(1)?)?;
+    let first = versions.partition_point(|(template_version, _)| template_version < latest);
+    Some(versions[first].1)
+}
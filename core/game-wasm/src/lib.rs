@@ -0,0 +1,5 @@
+//! JavaScript bindings of `game-core`, which are built by wasm-pack
+//!
+//! `game-core` stays a plain rlib for its no_std dependents like the contract, so the `cdylib`
+//! that wasm-pack requires is this wrapper instead
+pub use game_core::*;
@@ -1,16 +1,24 @@
-use alloc::{collections::BTreeMap, vec, vec::Vec};
-use common::enemy::EnemyLevel;
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    vec,
+    vec::Vec,
+};
+use common::{
+    enemy::EnemyLevel,
+    hardcoded::{MAX_SIGNALS_PER_OPERATION, MAX_TRANSFORM_DEPTH},
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    err,
     runtime::{RuntimePool, COUNTERPARTY_RUNTIME_ID},
-    Signal,
+    Error, Signal, SignalPriority,
 };
 
 #[cfg(feature = "log")]
-use crate::Log;
+use crate::{Log, SignalTrace};
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct GameStatistics {
     pub easy_mode_count: u16,
     pub normal_mode_count: u16,
@@ -47,6 +55,39 @@ impl RandGenerator {
     }
 }
 
+/// Bounds of signal processing, which stop effects that keep producing signals from hanging
+/// the game. Clients must use the same limits as the contract to get the same results
+#[derive(Clone, Copy)]
+pub struct SignalLimits {
+    pub max_signals_per_operation: u16,
+    pub max_transform_depth: u8,
+}
+
+impl Default for SignalLimits {
+    fn default() -> Self {
+        Self {
+            max_signals_per_operation: MAX_SIGNALS_PER_OPERATION,
+            max_transform_depth: MAX_TRANSFORM_DEPTH,
+        }
+    }
+}
+
+// Where a pending signal comes from, inherited from the signal that produced it, `transforms`
+// holds the effects which transformed its lineage in order
+#[derive(Clone, Default)]
+struct SignalOrigin {
+    transforms: Vec<u16>,
+    #[cfg(feature = "log")]
+    trace: Vec<SignalTrace>,
+}
+
+struct PendingSignal {
+    signal: Signal,
+    origin: SignalOrigin,
+    // Whether `before` hooks have run on it already
+    hooked: bool,
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct Context {
     pub rng: RandGenerator,
@@ -62,7 +103,25 @@ pub struct Context {
     battle_level: Option<EnemyLevel>,
     #[serde(skip)]
     #[serde(default)]
-    signals: Vec<Signal>,
+    // One queue for each `SignalPriority`, indexed by the priority
+    signals: [VecDeque<PendingSignal>; SignalPriority::COUNT],
+    // Origin of the signal being run
+    #[serde(skip)]
+    #[serde(default)]
+    origin: SignalOrigin,
+    // Origin that signals produced from now on inherit
+    #[serde(skip)]
+    #[serde(default)]
+    inherited_origin: SignalOrigin,
+    #[serde(skip)]
+    #[serde(default)]
+    hooked: bool,
+    #[serde(skip)]
+    #[serde(default)]
+    signal_count: u16,
+    #[serde(skip)]
+    #[serde(default)]
+    pub signal_limits: SignalLimits,
     #[serde(skip)]
     #[serde(default)]
     signal_history: BTreeMap<u8, Vec<Signal>>,
@@ -73,6 +132,10 @@ pub struct Context {
     #[serde(skip)]
     #[serde(default)]
     logs: Vec<Log>,
+    #[cfg(feature = "log")]
+    #[serde(skip)]
+    #[serde(default)]
+    operation_index: Option<u16>,
 }
 
 impl Context {
@@ -85,10 +148,73 @@ impl Context {
     }
 
     #[cfg(feature = "log")]
-    pub fn log(&mut self, log: Log) {
+    pub fn log(&mut self, mut log: Log) {
+        log.operation = self.operation_index.unwrap_or_default();
+        log.cause = self.inherited_origin.trace.clone();
         self.logs.push(log);
     }
 
+    /// Mark the start of a new player operation, which logs afterwards are grouped into and
+    /// signal limits are counted by
+    pub fn begin_operation(&mut self) {
+        #[cfg(feature = "log")]
+        {
+            self.operation_index = Some(self.operation_index.map_or(0, |index| index + 1));
+        }
+        self.signal_count = 0;
+        self.inherited_origin = SignalOrigin::default();
+    }
+
+    /// Continue operation indexes after `operation_count` operations, used when the runtime
+    /// is restored from a checkpoint
+    #[cfg(feature = "log")]
+    pub fn resume_operations(&mut self, operation_count: usize) {
+        self.operation_index = operation_count.checked_sub(1).map(|index| index as u16);
+        self.inherited_origin = SignalOrigin::default();
+    }
+
+    /// Make signals and logs produced afterwards originate from the signal being run, either
+    /// by runtimes running it or by the effect of `transformed_by` transforming it
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+    pub fn enter_signal(&mut self, signal: &Signal, transformed_by: Option<u16>) {
+        let mut origin = self.origin.clone();
+        if let Some(effect_runtime_id) = transformed_by {
+            origin.transforms.push(effect_runtime_id);
+        }
+        #[cfg(feature = "log")]
+        origin.trace.push(SignalTrace {
+            name: signal.name,
+            source_runtime_id: signal.source_runtime_id,
+            transformed_by,
+        });
+        self.inherited_origin = origin;
+    }
+
+    /// Check the transformation entered by `enter_signal` doesn't go too deep
+    ///
+    /// Effects skip lineages they have transformed already, see `transformed_by`, so no
+    /// transformation repeats and the depth is what bounds a lineage, since every transforming
+    /// effect adds one step to it
+    pub fn check_transform(&self) -> Result<(), Error> {
+        if self.inherited_origin.transforms.len() > self.signal_limits.max_transform_depth as usize
+        {
+            return Err(err!(SystemSignalLimitExceeded));
+        }
+        Ok(())
+    }
+
+    /// Whether the effect has transformed an ancestor of the signal being transformed, effects
+    /// only transform each lineage once so that they stack instead of repeating
+    ///
+    /// Called after `enter_signal` has pushed the step of the effect asking, so that step is
+    /// left out and only the ancestors are looked at
+    pub fn transformed_by(&self, effect_runtime_id: u16) -> bool {
+        self.inherited_origin
+            .transforms
+            .split_last()
+            .is_some_and(|(_, lineage)| lineage.contains(&effect_runtime_id))
+    }
+
     #[cfg(feature = "log")]
     pub fn dump_logs(&mut self) -> Vec<Log> {
         let mut logs = vec![];
@@ -99,14 +225,34 @@ impl Context {
     #[cfg(not(feature = "log"))]
     pub fn log(&self) {}
 
+    pub fn generate_runtime_id(&mut self) -> u16 {
+        self.runtime_id += 1;
+        self.runtime_id
+    }
+
     pub fn battle_running(&self) -> bool {
         self.battle_level.is_some()
     }
 
+    pub fn battle_round(&self) -> u8 {
+        self.current_battle_round
+    }
+
     pub fn battle_level(&self) -> Option<&EnemyLevel> {
         self.battle_level.as_ref()
     }
 
+    /// Enter a battle of the level, whose rounds are counted from 1
+    pub fn start_battle(&mut self, battle_level: EnemyLevel) {
+        self.battle_count = self.battle_count.saturating_add(1);
+        self.current_battle_round = 1;
+        self.battle_level = Some(battle_level);
+    }
+
+    pub fn next_round(&mut self) {
+        self.current_battle_round = self.current_battle_round.saturating_add(1);
+    }
+
     pub fn clear_battle_level(&mut self) {
         self.battle_level = None;
     }
@@ -117,14 +263,59 @@ impl Context {
     }
 
     pub fn signal(&mut self, signal: Signal) {
-        self.signals.push(signal);
+        self.signal_with_priority(signal, SignalPriority::Normal);
+    }
+
+    pub fn signal_with_priority(&mut self, signal: Signal, priority: SignalPriority) {
+        self.enqueue(
+            PendingSignal {
+                signal,
+                origin: self.inherited_origin.clone(),
+                hooked: false,
+            },
+            priority,
+        );
+    }
+
+    /// Queue the signal being run again after the interrupts returned by its `before` hooks
+    pub fn defer_signal(&mut self, signal: Signal) {
+        self.enqueue(
+            PendingSignal {
+                signal,
+                origin: self.origin.clone(),
+                hooked: true,
+            },
+            SignalPriority::Interrupt,
+        );
     }
 
-    pub fn pop_signal(&mut self) -> Option<Signal> {
-        if self.signals.is_empty() {
-            return None;
+    /// Whether `before` hooks have run on the signal being run
+    pub fn hooked(&self) -> bool {
+        self.hooked
+    }
+
+    // Behind every signal of the same or higher priority
+    fn enqueue(&mut self, pending: PendingSignal, priority: SignalPriority) {
+        self.signals[priority as usize].push_back(pending);
+    }
+
+    pub fn pop_signal(&mut self) -> Result<Option<Signal>, Error> {
+        let Some(PendingSignal {
+            signal,
+            origin,
+            hooked,
+        }) = self.signals.iter_mut().find_map(VecDeque::pop_front)
+        else {
+            return Ok(None);
+        };
+        if self.signal_count >= self.signal_limits.max_signals_per_operation {
+            return Err(err!(SystemSignalLimitExceeded));
         }
-        Some(self.signals.remove(0))
+        self.signal_count += 1;
+        self.inherited_origin = origin.clone();
+        self.origin = origin;
+        self.hooked = hooked;
+        Ok(Some(signal))
     }
 
     pub fn applied_signal(&mut self, signal: Signal) {
@@ -134,6 +325,13 @@ impl Context {
             .push(signal);
     }
 
+    /// Remove the runtime from the pool once the running operation has settled
+    pub fn delete_runtime(&mut self, runtime_id: u16) {
+        if !self.delete_runtime_history.contains(&runtime_id) {
+            self.delete_runtime_history.push(runtime_id);
+        }
+    }
+
     pub fn dump_history_delete_runtimes(&mut self) -> Vec<u16> {
         let mut delete_history = vec![];
         delete_history.append(&mut self.delete_runtime_history);
@@ -143,7 +341,7 @@ impl Context {
     pub fn clear(&mut self) {
         self.current_battle_round = 0;
         self.battle_level = None;
-        self.signals.clear();
+        self.signals.iter_mut().for_each(VecDeque::clear);
         self.signal_history.clear();
         self.delete_runtime_history.clear();
     }
@@ -127,5 +127,7 @@ define_error_enum!(
         SystemCardSelectionExceeded,
         SystemInsufficientActionPoint,
         SystemBattleInProgress,
+        SystemSignalLimitExceeded,
+        SystemArchiveIncompatible,
     }
 );
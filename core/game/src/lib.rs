@@ -3,19 +3,37 @@ extern crate alloc;
 
 mod context;
 mod error;
+mod pool;
 mod runtime;
 mod signal;
+mod view;
 
 #[cfg(feature = "log")]
 mod log;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 use context::*;
+use pool::*;
 use runtime::*;
 use signal::*;
+use view::*;
 
 #[cfg(feature = "log")]
 use log::*;
 
-pub use context::GameStatistics;
+pub use context::{GameStatistics, RandGenerator, SignalLimits};
 pub use error::Error;
-pub use runtime::PveSystemRuntime;
+pub use pool::{card_pool, card_template, enemy_pool, player_pool};
+pub use runtime::{
+    CardSelection, CardSelectionSource, GameSetup, LegalOperations, LegalSelection,
+    PveSystemRuntime,
+};
+pub use view::{BattleView, CardView, EffectView, EnemyView, GameView, LootCardView, PlayerView};
+
+#[cfg(feature = "log")]
+pub use log::{CardPile, Log, LogEvent, LogName};
+#[cfg(feature = "native")]
+pub use pool::install_pools;
+#[cfg(feature = "wasm")]
+pub use wasm::WasmPveSystemRuntime;
@@ -1,7 +1,9 @@
-use alloc::string::String;
-use common::enum_with_display;
+use alloc::{string::String, vec::Vec};
+use common::{effect::EffectName, enum_with_display};
 use serde::{Deserialize, Serialize};
 
+use crate::{CardSelection, SignalTrace};
+
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
@@ -19,6 +21,56 @@ enum_with_display!(
     }
 );
 
+enum_with_display!(
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    #[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub enum CardPile {
+        Deck,
+        Hand,
+        Grave,
+        Exile,
+        Equipment,
+        Sorcery,
+        Removed,
+    }
+);
+
+/// Structured payload of a log, variant and field names are part of the JSON format that
+/// clients read, so only add new ones instead of renaming
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
+pub enum LogEvent {
+    CardMove {
+        card: u16,
+        from: CardPile,
+        to: CardPile,
+    },
+    Damage {
+        source: u16,
+        target: u16,
+        amount: u16,
+        blocked: u16,
+    },
+    Heal {
+        source: u16,
+        target: u16,
+        amount: u16,
+    },
+    BuffAdded {
+        effect: u16,
+        parent: u16,
+        name: EffectName,
+        value: u8,
+        countdown: u8,
+    },
+    BuffApplied {
+        effect: u16,
+        parent: u16,
+    },
+    SelectCards(CardSelection),
+}
+
 enum_with_display!(
     #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
     #[cfg_attr(feature = "wasm", wasm_bindgen)]
@@ -66,10 +118,21 @@ enum_with_display!(
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct Log {
     pub name: LogName,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Free-form attribute of legacy logs, new logs carry `event` instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) attribute: Option<String>,
     pub value: Option<u16>,
     pub recipient: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub event: Option<LogEvent>,
+    /// Index of the operation that emitted this log
+    #[serde(default)]
+    pub operation: u16,
+    /// Signals leading to this log, from the one sent by the operation to the one being run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub cause: Vec<SignalTrace>,
 }
 
 #[cfg(feature = "wasm")]
@@ -89,4 +152,18 @@ impl Log {
     pub fn set_attribute(&mut self, value: Option<String>) {
         self.attribute = value;
     }
+
+    /// Causal chain in JSON
+    #[wasm_bindgen(getter = cause)]
+    pub fn cause_json(&self) -> String {
+        serde_json::to_string(&self.cause).expect("wasm log cause")
+    }
+
+    /// Structured event in JSON
+    #[wasm_bindgen(getter = event)]
+    pub fn event_json(&self) -> Option<String> {
+        self.event
+            .as_ref()
+            .map(|event| serde_json::to_string(event).expect("wasm log event"))
+    }
 }
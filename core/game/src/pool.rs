@@ -0,0 +1,60 @@
+use common::{
+    card::{CardConfig, CardName, CardPool},
+    enemy::EnemyPool,
+    player::PlayerPool,
+};
+use database::{CARD_POOL, ENEMY_POOL, PLAYER_POOL};
+use spin::Once;
+
+// Broken bins are cached as `None` as well, so they are neither parsed again nor panicking,
+// callers turn `None` into the error of what they were looking for
+static CARD_POOL_CACHE: Once<Option<CardPool>> = Once::new();
+static ENEMY_POOL_CACHE: Once<Option<EnemyPool>> = Once::new();
+static PLAYER_POOL_CACHE: Once<Option<PlayerPool>> = Once::new();
+
+/// Card pool parsed from database on the first access, and then shared by all lookups
+pub fn card_pool() -> Option<&'static CardPool> {
+    CARD_POOL_CACHE
+        .call_once(|| serde_molecule::from_slice(CARD_POOL, false).ok())
+        .as_ref()
+}
+
+/// Enemy pool parsed from database on the first access, and then shared by all lookups
+pub fn enemy_pool() -> Option<&'static EnemyPool> {
+    ENEMY_POOL_CACHE
+        .call_once(|| serde_molecule::from_slice(ENEMY_POOL, false).ok())
+        .as_ref()
+}
+
+/// Player pool parsed from database on the first access, and then shared by all lookups
+pub fn player_pool() -> Option<&'static PlayerPool> {
+    PLAYER_POOL_CACHE
+        .call_once(|| serde_molecule::from_slice(PLAYER_POOL, false).ok())
+        .as_ref()
+}
+
+/// Card template of the version looked up from the cached card pool
+pub fn card_template(name: CardName, version: u8) -> Option<&'static CardConfig> {
+    card_pool()?.card_by_name(name, version)
+}
+
+/// Use the given pools instead of the database bins, which only takes effect before the first
+/// lookup of each pool and returns whether all of them are taken. Native tools and tests play
+/// their own content with this, while the contract always reads the database
+#[cfg(feature = "native")]
+pub fn install_pools(card_pool: CardPool, enemy_pool: EnemyPool, player_pool: PlayerPool) -> bool {
+    let card_installed = install(&CARD_POOL_CACHE, card_pool);
+    let enemy_installed = install(&ENEMY_POOL_CACHE, enemy_pool);
+    let player_installed = install(&PLAYER_POOL_CACHE, player_pool);
+    card_installed && enemy_installed && player_installed
+}
+
+#[cfg(feature = "native")]
+fn install<T>(cache: &Once<Option<T>>, pool: T) -> bool {
+    let mut installed = false;
+    cache.call_once(|| {
+        installed = true;
+        Some(pool)
+    });
+    installed
+}
@@ -1,7 +1,10 @@
-use alloc::{collections::BTreeMap, vec::Vec};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
 use serde::{Deserialize, Serialize};
 
-use crate::{Context, Error, Signal};
+use crate::{
+    Context, Error, Signal, SignalName, SignalPriority, SignalValue, BATTLE_RUNTIME_ID,
+    PLAYER_RUNTIME_ID,
+};
 
 #[derive(Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -11,7 +14,25 @@ pub struct PveBattleRuntime {
 }
 
 impl PveBattleRuntime {
-    pub fn run(&mut self, _signal: &Signal, _ctx: &mut Context) -> Result<(), Error> {
+    pub const SUBSCRIPTIONS: &'static [SignalName] = &[SignalName::EnemyTurn];
+
+    /// A round is over after the enemy turn, then the player takes the next turn once every
+    /// signal of enemies has been resolved
+    pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        if signal.name == SignalName::EnemyTurn {
+            ctx.next_round();
+            ctx.signal_with_priority(Self::player_turn(), SignalPriority::Low);
+        }
         Ok(())
     }
+
+    pub fn player_turn() -> Signal {
+        Signal {
+            name: SignalName::PlayerTurn,
+            value: SignalValue::Empty,
+            transformed: false,
+            source_runtime_id: BATTLE_RUNTIME_ID,
+            target_runtime_ids: vec![PLAYER_RUNTIME_ID],
+        }
+    }
 }
@@ -0,0 +1,35 @@
+use alloc::{vec, vec::Vec};
+use common::effect::{Effect, EffectName, TrapTrigger};
+
+use super::set_trap;
+use crate::{spelled_from_player, CardRuntime, Context, Error, Implementation, Signal, SignalName};
+
+/// Set a face-down trap which strikes back once the owner is attacked
+///
+/// @zh 伏击
+#[derive(Default)]
+pub struct Ambush {}
+
+impl Implementation for Ambush {
+    fn run(
+        &mut self,
+        signal: &Signal,
+        card: &CardRuntime,
+        _: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        spelled_from_player!(signal, card);
+        let damage = card.param_value(0)?;
+        Ok(vec![set_trap(
+            card,
+            Effect {
+                name: EffectName::Counterattack,
+                trap: true,
+                trigger: Some(TrapTrigger::Attacked),
+                owner_source: None,
+                owner_target: None,
+                value: Some(damage as u8),
+                countdown: None,
+            },
+        )])
+    }
+}
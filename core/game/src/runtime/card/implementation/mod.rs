@@ -1,7 +1,34 @@
+use alloc::{vec, vec::Vec};
+use common::effect::Effect;
+
+use crate::{CardRuntime, Signal, SignalName, SignalValue};
+
+mod ambush;
 mod attack;
 
+pub use ambush::*;
 pub use attack::*;
 
+/// Signal of adding `effect` to each of `parent_runtime_ids` by the card
+pub fn add_effect(card: &CardRuntime, effect: Effect, parent_runtime_ids: Vec<u16>) -> Signal {
+    Signal {
+        name: SignalName::AddEffect,
+        value: SignalValue::Effect(effect),
+        transformed: false,
+        source_runtime_id: card.runtime_id,
+        target_runtime_ids: parent_runtime_ids,
+    }
+}
+
+/// Signal of setting `effect` face-down to the owner of trap card
+pub fn set_trap(card: &CardRuntime, effect: Effect) -> Signal {
+    let trap = Effect {
+        trap: true,
+        ..effect
+    };
+    add_effect(card, trap, vec![card.parent_runtime_id])
+}
+
 #[macro_export]
 macro_rules! spelled_from_player {
     ($signal:ident, $card:ident) => {
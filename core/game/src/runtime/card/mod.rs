@@ -1,10 +1,12 @@
 use alloc::{boxed::Box, format, vec::Vec};
-use common::card::{get_card_template_by_name, instance_card_by_seed, Card, CardName};
+use common::{
+    card::{decode_dna, encode_dna, Card, CardName},
+    hardcoded::DNA,
+};
 use core::cell::RefCell;
-use database::CARD_POOL;
 use serde::{Deserialize, Serialize};
 
-use crate::{err, Context, Error, Signal};
+use crate::{card_pool, err, Context, Error, Signal, SignalName};
 
 macro_rules! impls {
     ($var:ident, [$($name:ident,)+]) => {
@@ -28,6 +30,7 @@ pub trait Implementation: Send + Sync {
 
 pub struct CardRuntime {
     pub raw: Card,
+    pub version: u8,
     pub parent_runtime_id: u16,
     pub runtime_id: u16,
     pub cost: u8,
@@ -43,8 +46,8 @@ impl Serialize for CardRuntime {
     {
         #[derive(Serialize)]
         struct CardRuntimeHelper {
-            seed: u64,
-            card_name: CardName,
+            dna: DNA,
+            version: u8,
             parent_runtime_id: u16,
             runtime_id: u16,
             cost: u8,
@@ -52,8 +55,8 @@ impl Serialize for CardRuntime {
             awake: u8,
         }
         let helper = CardRuntimeHelper {
-            seed: self.raw.seed,
-            card_name: self.raw.name,
+            dna: encode_dna(&self.raw),
+            version: self.version,
             parent_runtime_id: self.parent_runtime_id,
             runtime_id: self.runtime_id,
             cost: self.cost,
@@ -71,8 +74,8 @@ impl<'de> Deserialize<'de> for CardRuntime {
     {
         #[derive(Deserialize)]
         struct CardRuntimeHelper {
-            seed: u64,
-            card_name: CardName,
+            dna: DNA,
+            version: u8,
             parent_runtime_id: u16,
             runtime_id: u16,
             cost: u8,
@@ -80,12 +83,15 @@ impl<'de> Deserialize<'de> for CardRuntime {
             awake: u8,
         }
         let helper = CardRuntimeHelper::deserialize(deserializer)?;
-        let implementation = RefCell::new(CardFactory::create_implementation(&helper.card_name));
-        let template = get_card_template_by_name(CARD_POOL, helper.card_name).ok_or_else(|| {
-            serde::de::Error::custom(format!("card template not found: {}", helper.card_name))
-        })?;
+        let card = card_pool()
+            .and_then(|card_pool| decode_dna(card_pool, &helper.dna, helper.version))
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!("card dna not decoded: {:?}", helper.dna))
+            })?;
+        let implementation = RefCell::new(CardFactory::create_implementation(&card.name));
         Ok(CardRuntime {
-            raw: instance_card_by_seed(&template, helper.seed),
+            raw: card,
+            version: helper.version,
             parent_runtime_id: helper.parent_runtime_id,
             runtime_id: helper.runtime_id,
             cost: helper.cost,
@@ -101,6 +107,7 @@ impl core::fmt::Debug for CardRuntime {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("CardRuntime")
             .field("raw", &self.raw)
+            .field("version", &self.version)
             .field("parent_runtime_id", &self.parent_runtime_id)
             .field("runtime_id", &self.runtime_id)
             .field("cost", &self.cost)
@@ -112,7 +119,26 @@ impl core::fmt::Debug for CardRuntime {
 }
 
 impl CardRuntime {
-    pub fn run(&mut self, _signal: &Signal, _ctx: &mut Context) -> Result<(), Error> {
+    pub const SUBSCRIPTIONS: &'static [SignalName] = &[SignalName::SpellCard];
+
+    pub fn new(card: Card, version: u8, parent_runtime_id: u16, runtime_id: u16) -> Self {
+        let implementation = RefCell::new(CardFactory::create_implementation(&card.name));
+        Self {
+            version,
+            parent_runtime_id,
+            runtime_id,
+            cost: card.cost,
+            exile: card.exile,
+            awake: 0,
+            raw: card,
+            _implementation: implementation,
+        }
+    }
+
+    /// Signals returned by the implementation of card are queued in order
+    pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        let signals = self._implementation.borrow_mut().run(signal, self, ctx)?;
+        signals.into_iter().for_each(|signal| ctx.signal(signal));
         Ok(())
     }
 
@@ -129,6 +155,6 @@ pub struct CardFactory {}
 
 impl CardFactory {
     fn create_implementation(card_name: &CardName) -> Box<dyn Implementation> {
-        impls!(card_name, [Attack,])
+        impls!(card_name, [Attack, Ambush,])
     }
 }
@@ -1,37 +1,360 @@
 use alloc::{vec, vec::Vec};
 
 use crate::{
-    runtime::effect::{default_transform, EffectRuntime, Feature},
-    Context, Error, Signal, SignalName, SignalValue,
+    runtime::effect::{default_transform, EffectRuntime, Feature, FeatureFlags},
+    Context, Error, RuntimeType, Signal, SignalName, SignalValue,
 };
 
-/// Power up the damage caused by the owner of card to any enemies
+fn damage(signal: &Signal) -> Option<u16> {
+    match (signal.name, &signal.value) {
+        (SignalName::ChangeHp, SignalValue::Negative(damage)) => Some(*damage),
+        _ => None,
+    }
+}
+
+// Replace the damage of `signal`, the source is kept so that other effects filtering by owner
+// still see who caused it
+fn change_damage(
+    damage: u16,
+    signal: &Signal,
+    effect: &EffectRuntime,
+    ctx: &mut Context,
+) -> Result<Vec<Signal>, Error> {
+    default_transform(
+        vec![Signal {
+            name: SignalName::ChangeHp,
+            value: SignalValue::Negative(damage),
+            transformed: false,
+            source_runtime_id: signal.source_runtime_id,
+            target_runtime_ids: signal.target_runtime_ids.clone(),
+        }],
+        signal,
+        effect,
+        ctx,
+    )
+}
+
+// Owner of whoever damaged the parent directly, damage from effects is ignored so that
+// reactions of both sides don't bounce forever
+fn attacker(signal: &Signal, effect: &EffectRuntime, ctx: &Context) -> Option<u16> {
+    damage(signal)?;
+    let source = ctx.runtimes.get(&signal.source_runtime_id).ok()?;
+    if !signal.is_target(effect.parent_runtime_id) || source.runtime_type() == RuntimeType::Effect {
+        return None;
+    }
+    let attacker = ctx.runtimes.owner_runtime_id(signal.source_runtime_id);
+    ctx.runtimes
+        .opponents(attacker, effect.parent_runtime_id)
+        .then_some(attacker)
+}
+
+// Add the value of effect to damage, shared by features powering damage up
+fn extra_damage(
+    signal: &Signal,
+    effect: &EffectRuntime,
+    ctx: &mut Context,
+) -> Result<Vec<Signal>, Error> {
+    let Some(damage) = damage(signal) else {
+        return Ok(vec![]);
+    };
+    change_damage(
+        damage.saturating_add(effect.value as u16),
+        signal,
+        effect,
+        ctx,
+    )
+}
+
+// Signal sent to the parent by the effect at each `turn`, which is `EnemyTurn` for those
+// ticking at the end of each round
+fn turn_tick(
+    signal: &Signal,
+    effect: &EffectRuntime,
+    turn: SignalName,
+    name: SignalName,
+    value: SignalValue,
+) -> Vec<Signal> {
+    if signal.name != turn {
+        return vec![];
+    }
+    vec![Signal {
+        name,
+        value,
+        transformed: false,
+        source_runtime_id: effect.runtime_id,
+        target_runtime_ids: vec![effect.parent_runtime_id],
+    }]
+}
+
+/// Power up the damage caused by the owner of card to any enemies, which is configured with
+/// `owner_source` of `true` so that damage from others isn't powered up
 ///
 /// @zh 指定回合内增加卡牌伤害
 #[derive(Default)]
 pub struct ExtraDamage {}
 
 impl Feature for ExtraDamage {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::TRANSFORM
+    }
+
+    fn transform(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        extra_damage(signal, effect, ctx)
+    }
+}
+
+/// Deal damage back to the attacker, which is meant to be set as a trap
+///
+/// @zh 反击
+#[derive(Default)]
+pub struct Counterattack {}
+
+impl Feature for Counterattack {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::SPRING
+    }
+
+    fn spring(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        let attacker = ctx.runtimes.owner_runtime_id(signal.source_runtime_id);
+        // Struck back by the owner, so that effects filtering by owner treat it as the owner's
+        // damage, while the trap itself is consumed already
+        Ok(vec![Signal {
+            name: SignalName::ChangeHp,
+            value: SignalValue::Negative(effect.value as u16),
+            transformed: false,
+            source_runtime_id: effect.parent_runtime_id,
+            target_runtime_ids: vec![attacker],
+        }])
+    }
+}
+
+/// Lose hp of value at the end of each round, ignoring block
+///
+/// @zh 中毒
+#[derive(Default)]
+pub struct Poison {}
+
+impl Feature for Poison {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::AFTER
+    }
+
+    fn after(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        _: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(turn_tick(
+            signal,
+            effect,
+            SignalName::EnemyTurn,
+            SignalName::ChangeRealHp,
+            SignalValue::Negative(effect.value as u16),
+        ))
+    }
+}
+
+/// Take damage of value at the end of each round, which can be blocked
+///
+/// @zh 流血
+#[derive(Default)]
+pub struct Bleed {}
+
+impl Feature for Bleed {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::AFTER
+    }
+
+    fn after(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        _: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(turn_tick(
+            signal,
+            effect,
+            SignalName::EnemyTurn,
+            SignalName::ChangeHp,
+            SignalValue::Negative(effect.value as u16),
+        ))
+    }
+}
+
+/// Reduce damage by value in percent, configured with `owner_source` of `true` to weaken the
+/// damage caused by the owner
+///
+/// @zh 虚弱
+#[derive(Default)]
+pub struct Weak {}
+
+impl Feature for Weak {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::TRANSFORM
+    }
+
     fn transform(
         &self,
         signal: &Signal,
         effect: &EffectRuntime,
         ctx: &mut Context,
     ) -> Result<Vec<Signal>, Error> {
-        if signal.name != SignalName::ChangeHp {
+        let Some(damage) = damage(signal) else {
             return Ok(vec![]);
-        }
-        let SignalValue::Negative(damage) = signal.value else {
+        };
+        let reduced = (damage as u32 * effect.value.min(100) as u32 / 100) as u16;
+        change_damage(damage - reduced, signal, effect, ctx)
+    }
+}
+
+/// Increase damage by value in percent, configured with `owner_target` of `true` to make the
+/// owner take more damage
+///
+/// @zh 易伤
+#[derive(Default)]
+pub struct Vulnerable {}
+
+impl Feature for Vulnerable {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::TRANSFORM
+    }
+
+    fn transform(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        let Some(damage) = damage(signal) else {
             return Ok(vec![]);
         };
-        let damage = damage.saturating_add(effect.value as u16);
+        let increased = (damage as u32 * effect.value as u32 / 100) as u16;
+        change_damage(damage.saturating_add(increased), signal, effect, ctx)
+    }
+}
+
+/// Add value to every damage, configured with `owner_source` of `true` for the damage caused
+/// by the owner, which lasts the whole battle unless a countdown is given
+///
+/// @zh 力量
+#[derive(Default)]
+pub struct Strength {}
+
+impl Feature for Strength {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::TRANSFORM
+    }
+
+    fn transform(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        extra_damage(signal, effect, ctx)
+    }
+}
+
+/// Deal damage of value back to whoever attacks the owner, every time right before the attack
+/// lands
+///
+/// @zh 荆棘
+#[derive(Default)]
+pub struct Thorns {}
+
+impl Feature for Thorns {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::BEFORE
+    }
+
+    fn before(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        let Some(attacker) = attacker(signal, effect, ctx) else {
+            return Ok(vec![]);
+        };
+        Ok(vec![Signal {
+            name: SignalName::ChangeHp,
+            value: SignalValue::Negative(effect.value as u16),
+            transformed: false,
+            source_runtime_id: effect.runtime_id,
+            target_runtime_ids: vec![attacker],
+        }])
+    }
+}
+
+/// Recover hp of value at the end of each round
+///
+/// @zh 再生
+#[derive(Default)]
+pub struct Regeneration {}
+
+impl Feature for Regeneration {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::AFTER
+    }
+
+    fn after(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        _: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(turn_tick(
+            signal,
+            effect,
+            SignalName::EnemyTurn,
+            SignalName::ChangeHp,
+            SignalValue::Positive(effect.value as u16),
+        ))
+    }
+}
+
+/// Skip the actions of the owner enemy, which are the signals sent by the enemy itself
+///
+/// @zh 眩晕
+#[derive(Default)]
+pub struct Stun {}
+
+impl Feature for Stun {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::TRANSFORM
+    }
+
+    fn parent_type(&self) -> Option<RuntimeType> {
+        Some(RuntimeType::Enemy)
+    }
+
+    fn transform(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        if signal.source_runtime_id != effect.parent_runtime_id {
+            return Ok(vec![]);
+        }
         default_transform(
             vec![Signal {
-                name: SignalName::ChangeHp,
-                value: SignalValue::Negative(damage),
-                transformed: true,
+                name: SignalName::Skip,
+                value: SignalValue::Empty,
+                transformed: false,
                 source_runtime_id: effect.runtime_id,
-                target_runtime_ids: signal.target_runtime_ids.clone(),
+                target_runtime_ids: vec![],
             }],
             signal,
             effect,
@@ -39,3 +362,34 @@ impl Feature for ExtraDamage {
         )
     }
 }
+
+/// Gain energy of value at the start of each turn of the player, after energy is refilled
+///
+/// @zh 能量获取
+#[derive(Default)]
+pub struct EnergyGain {}
+
+impl Feature for EnergyGain {
+    fn flags(&self) -> FeatureFlags {
+        FeatureFlags::AFTER
+    }
+
+    fn parent_type(&self) -> Option<RuntimeType> {
+        Some(RuntimeType::Player)
+    }
+
+    fn after(
+        &self,
+        signal: &Signal,
+        effect: &EffectRuntime,
+        _: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(turn_tick(
+            signal,
+            effect,
+            SignalName::PlayerTurn,
+            SignalName::ChangeEnergy,
+            SignalValue::Positive(effect.value as u16),
+        ))
+    }
+}
@@ -1,10 +1,13 @@
 use core::cell::RefCell;
 
 use alloc::{boxed::Box, vec, vec::Vec};
-use common::effect::{Effect, EffectName};
+use common::effect::{Effect, EffectName, TrapTrigger};
 use serde::{Deserialize, Serialize};
 
-use crate::{Context, Error, Signal};
+use crate::{
+    err, Context, Error, Runtime, RuntimePool, RuntimeType, Signal, SignalHook, SignalName,
+    SignalValue,
+};
 
 mod feature;
 
@@ -21,16 +24,61 @@ pub fn _default_run(_: &Signal, _: &EffectRuntime) -> Result<Vec<Signal>, Error>
 }
 
 pub fn default_transform(
-    _: Vec<Signal>,
+    signals: Vec<Signal>,
     _: &Signal,
     _: &EffectRuntime,
     _: &mut Context,
 ) -> Result<Vec<Signal>, Error> {
-    Ok(vec![])
+    Ok(signals)
+}
+
+/// Steps of signal resolution that a feature implements, effects are only visited by the
+/// system at those steps
+#[derive(Clone, Copy, Default)]
+pub struct FeatureFlags {
+    pub transform: bool,
+    pub before: bool,
+    pub after: bool,
+    pub spring: bool,
+}
+
+impl FeatureFlags {
+    pub const TRANSFORM: Self = Self {
+        transform: true,
+        before: false,
+        after: false,
+        spring: false,
+    };
+    pub const BEFORE: Self = Self {
+        transform: false,
+        before: true,
+        after: false,
+        spring: false,
+    };
+    pub const AFTER: Self = Self {
+        transform: false,
+        before: false,
+        after: true,
+        spring: false,
+    };
+    pub const SPRING: Self = Self {
+        transform: false,
+        before: false,
+        after: false,
+        spring: true,
+    };
 }
 
 #[allow(unused)]
 pub trait Feature: Send + Sync {
+    /// Which of `transform`, `before`, `after` and `spring` are implemented
+    fn flags(&self) -> FeatureFlags;
+
+    /// Type of the only parent this feature works with, `None` accepts any parent
+    fn parent_type(&self) -> Option<RuntimeType> {
+        None
+    }
+
     fn run(
         &mut self,
         signal: &Signal,
@@ -48,6 +96,36 @@ pub trait Feature: Send + Sync {
     ) -> Result<Vec<Signal>, Error> {
         default_transform(vec![], signal, effect, ctx)
     }
+
+    /// Signals to resolve right before `signal` is run by runtimes
+    fn before(
+        &self,
+        _signal: &Signal,
+        _effect: &EffectRuntime,
+        _ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(vec![])
+    }
+
+    /// Signals to resolve right after `signal` is run by runtimes, such as counter-attacks
+    fn after(
+        &self,
+        _signal: &Signal,
+        _effect: &EffectRuntime,
+        _ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(vec![])
+    }
+
+    /// Payload signals of a trap, fired once when `signal` meets its trigger
+    fn spring(
+        &self,
+        _signal: &Signal,
+        _effect: &EffectRuntime,
+        _ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        Ok(vec![])
+    }
 }
 
 #[derive(Serialize)]
@@ -102,12 +180,167 @@ impl core::fmt::Debug for EffectRuntime {
 }
 
 impl EffectRuntime {
-    pub fn run(&mut self, _signal: &Signal, _ctx: &mut Context) -> Result<(), Error> {
+    pub const SUBSCRIPTIONS: &'static [SignalName] =
+        &[SignalName::EnemyTurn, SignalName::BattleOver];
+
+    pub fn new(effect: Effect, parent_runtime_id: u16, runtime_id: u16) -> Result<Self, Error> {
+        if effect.trap && effect.trigger.is_none() {
+            return Err(err!(EffectInvalidParameter(effect.name)));
+        }
+        if effect.countdown == Some(0) {
+            return Err(err!(EffectUnexpectedZeroCountdown(effect.name)));
+        }
+        Ok(Self {
+            raw: effect,
+            runtime_id,
+            parent_runtime_id,
+            value: effect.value.unwrap_or_default(),
+            countdown: effect.countdown.unwrap_or_default(),
+            _feature: RefCell::new(EffectFactory::create_feature(&effect.name)),
+        })
+    }
+
+    /// Effects only last in the battle they are added, and those with countdown expire after
+    /// that many rounds
+    pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        if signal.name == SignalName::BattleOver {
+            ctx.delete_runtime(self.runtime_id);
+            return Ok(());
+        }
+        if signal.name != SignalName::EnemyTurn || self.raw.countdown.is_none() {
+            return Ok(());
+        }
+        if self.countdown == 0 {
+            return Err(err!(EffectUnexpectedZeroCountdown(self.raw.name)));
+        }
+        self.countdown -= 1;
+        if self.countdown == 0 {
+            ctx.delete_runtime(self.runtime_id);
+        }
         Ok(())
     }
 
-    pub fn transform(&self, _signal: &Signal, _ctx: &mut Context) -> Result<Vec<Signal>, Error> {
-        Ok(vec![])
+    /// Steps this effect takes part in, face-down traps only spring
+    pub fn flags(&self) -> FeatureFlags {
+        let flags = self._feature.borrow().flags();
+        if self.raw.trap {
+            FeatureFlags {
+                spring: flags.spring,
+                ..Default::default()
+            }
+        } else {
+            FeatureFlags {
+                spring: false,
+                ..flags
+            }
+        }
+    }
+
+    pub fn transform(&self, signal: &Signal, ctx: &mut Context) -> Result<Vec<Signal>, Error> {
+        if self.raw.trap
+            || ctx.transformed_by(self.runtime_id)
+            || !self.owner_matches(signal, &ctx.runtimes)?
+        {
+            return Ok(vec![]);
+        }
+        self._feature.borrow().transform(signal, self, ctx)
+    }
+
+    pub fn hook(
+        &self,
+        hook: SignalHook,
+        signal: &Signal,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        // Face-down traps only react through `spring`, and turns belong to nobody, so owner
+        // filters don't stop the ticks of effects
+        let turn = matches!(signal.name, SignalName::EnemyTurn | SignalName::PlayerTurn);
+        if self.raw.trap || (!turn && !self.owner_matches(signal, &ctx.runtimes)?) {
+            return Ok(vec![]);
+        }
+        let feature = self._feature.borrow();
+        match hook {
+            SignalHook::Before => feature.before(signal, self, ctx),
+            SignalHook::After => feature.after(signal, self, ctx),
+        }
+    }
+
+    /// Payload signals if this is a trap and `signal` meets its trigger, otherwise `None`
+    pub fn spring(&self, signal: &Signal, ctx: &mut Context) -> Result<Option<Vec<Signal>>, Error> {
+        if !self.raw.trap || !self.triggered(signal, &ctx.runtimes) {
+            return Ok(None);
+        }
+        let payload = self._feature.borrow().spring(signal, self, ctx)?;
+        Ok(Some(payload))
+    }
+
+    /// Effects with owner filters only apply to the player or enemies, since the battle owns
+    /// nothing to filter by, and features of a specific parent type only apply to that type
+    pub fn check_parent(&self, parent: &Runtime) -> Result<(), Error> {
+        let parent_type = self._feature.borrow().parent_type();
+        if parent_type.is_some_and(|parent_type| parent_type != parent.runtime_type()) {
+            return Err(err!(EffectInvalidParent(self.raw.name)));
+        }
+        let filtered = self.raw.owner_source.is_some() || self.raw.owner_target.is_some();
+        match parent.runtime_type() {
+            RuntimeType::Player | RuntimeType::Enemy => Ok(()),
+            RuntimeType::PveBattle if !filtered => Ok(()),
+            _ => Err(err!(EffectInvalidParent(self.raw.name))),
+        }
+    }
+
+    /// Whether the source and targets of `signal` belong to the parent as `owner_source` and
+    /// `owner_target` require, `None` accepts either
+    pub fn owner_matches(&self, signal: &Signal, runtimes: &RuntimePool) -> Result<bool, Error> {
+        if self.raw.owner_source.is_none() && self.raw.owner_target.is_none() {
+            return Ok(true);
+        }
+        let parent = self.parent_runtime_id;
+        // Effects of a removed parent are on their way out
+        let Ok(parent_runtime) = runtimes.get(&parent) else {
+            return Ok(false);
+        };
+        self.check_parent(parent_runtime)?;
+        let source_owned = runtimes.owner_runtime_id(signal.source_runtime_id) == parent;
+        let target_owned = signal
+            .target_runtime_ids
+            .iter()
+            .any(|runtime_id| runtimes.owner_runtime_id(*runtime_id) == parent);
+        let source_matched = self
+            .raw
+            .owner_source
+            .map_or(true, |owned| owned == source_owned);
+        let target_matched = self
+            .raw
+            .owner_target
+            .map_or(true, |owned| owned == target_owned);
+        Ok(source_matched && target_matched)
+    }
+
+    fn triggered(&self, signal: &Signal, runtimes: &RuntimePool) -> bool {
+        let Some(trigger) = self.raw.trigger else {
+            return false;
+        };
+        let owner = self.parent_runtime_id;
+        let source_owner = runtimes.owner_runtime_id(signal.source_runtime_id);
+        match trigger {
+            TrapTrigger::Attacked => {
+                signal.name == SignalName::ChangeHp
+                    && matches!(signal.value, SignalValue::Negative(_))
+                    && signal.is_target(owner)
+                    && runtimes.opponents(source_owner, owner)
+            }
+            TrapTrigger::OpponentBuffed => {
+                signal.name == SignalName::AddEffect && runtimes.opponents(source_owner, owner)
+            }
+            TrapTrigger::HpBelow(percent) => {
+                signal.name == SignalName::ChangeHp
+                    && signal.is_target(owner)
+                    && runtimes
+                        .get(&owner)
+                        .is_ok_and(|parent| parent.hp_percent().is_some_and(|hp| hp < percent))
+            }
+        }
     }
 }
 
@@ -115,6 +348,21 @@ pub struct EffectFactory {}
 
 impl EffectFactory {
     fn create_feature(effect_name: &EffectName) -> Box<dyn Feature> {
-        impls!(effect_name, [ExtraDamage,])
+        impls!(
+            effect_name,
+            [
+                ExtraDamage,
+                Counterattack,
+                Poison,
+                Bleed,
+                Weak,
+                Vulnerable,
+                Strength,
+                Thorns,
+                Regeneration,
+                Stun,
+                EnergyGain,
+            ]
+        )
     }
 }
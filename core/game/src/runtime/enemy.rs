@@ -1,9 +1,12 @@
-use alloc::vec::Vec;
-use common::enemy::{Action, Enemy, WeightedAction};
+use alloc::{vec, vec::Vec};
+use common::enemy::{roulette_action, Action, Enemy, WeightedAction};
 use serde::{Deserialize, Serialize};
 use serde_molecule::dynvec_serde;
 
-use crate::{Context, Error, Signal};
+use crate::{
+    change_hp, err, iter_stats, Context, Error, RandGenerator, Signal, SignalName, SignalValue,
+    PLAYER_RUNTIME_ID,
+};
 
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -24,7 +27,167 @@ pub struct EnemyRuntime {
 }
 
 impl EnemyRuntime {
-    pub fn run(&mut self, _signal: &Signal, _ctx: &mut Context) -> Result<(), Error> {
+    pub const SUBSCRIPTIONS: &'static [SignalName] = &[
+        SignalName::EnemyTurn,
+        SignalName::ChangeHp,
+        SignalName::ChangeRealHp,
+    ];
+
+    pub fn new(enemy: Enemy, runtime_id: u16) -> Self {
+        let action_pool = enemy
+            .actions
+            .iter()
+            .map(|action| WeightedAction {
+                raw: action.clone(),
+                weight: action.weight,
+                use_count: 0,
+                condition_hit: Default::default(),
+            })
+            .collect();
+        Self {
+            runtime_id,
+            hp: enemy.hp,
+            attack: enemy.attack,
+            defense: enemy.defense,
+            spirit: enemy.spirit,
+            block: 0,
+            shield: 0,
+            action_pool,
+            active_actions: Vec::new(),
+            active_effects: Vec::new(),
+            raw: enemy,
+        }
+    }
+
+    /// Dead enemies stay in the battle until it is over, but neither act nor take hp changes
+    pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        if self.hp == 0 || !signal.is_target(self.runtime_id) {
+            return Ok(());
+        }
+        match signal.name {
+            SignalName::EnemyTurn => self.take_turn(ctx),
+            SignalName::ChangeHp | SignalName::ChangeRealHp => {
+                let change = change_hp(
+                    signal,
+                    &mut self.hp,
+                    self.raw.hp,
+                    &mut self.block,
+                    &mut self.shield,
+                )
+                .ok_or(err!(EnemyInvalidSignalValue(signal.name)))?;
+                iter_stats!(ctx, casued_damage, change.lost);
+                change.log(signal, self.runtime_id, ctx);
+                if self.hp == 0 {
+                    iter_stats!(ctx, killed_enemy_count, 1);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pick the action of next turn by weight among the actions with uses left, which is shown
+    /// to the player as `active_actions` until it is taken. Enemies without any action left
+    /// stay idle, and weight tweakers are not applied yet
+    pub fn roll_actions(&mut self, rng: &mut RandGenerator) {
+        fn available(action: &WeightedAction) -> bool {
+            action.weight > 0
+                && action
+                    .raw
+                    .amount
+                    .map_or(true, |amount| action.use_count < amount)
+        }
+        self.active_actions.clear();
+        let total_weight = self
+            .action_pool
+            .iter()
+            .filter(|action| available(action))
+            .map(|action| action.weight as u64)
+            .sum::<u64>();
+        if total_weight == 0 {
+            return;
+        }
+        let mut point = rng.next_u64() % total_weight;
+        let Some(action) = self
+            .action_pool
+            .iter_mut()
+            .filter(|action| available(action))
+            .find(|action| {
+                let picked = point < action.weight as u64;
+                point = point.saturating_sub(action.weight as u64);
+                picked
+            })
+        else {
+            return;
+        };
+        action.use_count += 1;
+        self.active_actions
+            .push(roulette_action(&action.raw.action, rng.next_u64()));
+    }
+
+    // Block lasts until the next turn of the enemy, when the telegraphed actions are taken
+    fn take_turn(&mut self, ctx: &mut Context) -> Result<(), Error> {
+        self.block = 0;
+        for action in core::mem::take(&mut self.active_actions) {
+            self.act(&action, ctx)?;
+        }
+        self.roll_actions(&mut ctx.rng);
         Ok(())
     }
+
+    // Attacks and effects are sent as signals, so that effects can transform or react to them,
+    // while attributes of the enemy itself are changed in place
+    fn act(&mut self, action: &Action, ctx: &mut Context) -> Result<(), Error> {
+        match action {
+            Action::UseAttack(damage) => ctx.signal(self.attack_signal(*damage)),
+            Action::UseMultipleAttack(damage, times) => {
+                (0..*times).for_each(|_| ctx.signal(self.attack_signal(*damage)));
+            }
+            Action::UseDefense(block) => {
+                self.block = self
+                    .block
+                    .saturating_add(*block as u16 + self.defense as u16);
+            }
+            Action::UseShield(shield) | Action::AddEnemeyShield(shield) => {
+                self.shield = self.shield.saturating_add(*shield as u16);
+            }
+            Action::AddEnemyAttack(attack) => self.attack = self.attack.saturating_add(*attack),
+            Action::AddEnemyDefense(defense) => {
+                self.defense = self.defense.saturating_add(*defense);
+            }
+            Action::UseEffect(_) | Action::AddEnemeyEffect(_) => {
+                ctx.signal(self.effect_signal(action).ok_or(err!(Internal))?);
+            }
+            // Summoned enemies have no place to join the battle yet
+            Action::SummonCreature(..) => return Err(err!(ActionCreateFailed)),
+        }
+        Ok(())
+    }
+
+    fn attack_signal(&self, damage: u8) -> Signal {
+        Signal {
+            name: SignalName::ChangeHp,
+            value: SignalValue::Negative(damage as u16 + self.attack as u16),
+            transformed: false,
+            source_runtime_id: self.runtime_id,
+            target_runtime_ids: vec![PLAYER_RUNTIME_ID],
+        }
+    }
+
+    /// Signal of adding the effect of an effect action, `UseEffect` casts onto the player and
+    /// `AddEnemeyEffect` onto the enemy itself
+    pub fn effect_signal(&self, action: &Action) -> Option<Signal> {
+        let (effect, parent_runtime_id) = match action {
+            Action::UseEffect(effect) => (effect, PLAYER_RUNTIME_ID),
+            Action::AddEnemeyEffect(effect) => (effect, self.runtime_id),
+            _ => return None,
+        };
+        Some(Signal {
+            name: SignalName::AddEffect,
+            value: SignalValue::Effect(*effect),
+            transformed: false,
+            source_runtime_id: self.runtime_id,
+            target_runtime_ids: vec![parent_runtime_id],
+        })
+    }
 }
@@ -1,7 +1,10 @@
-use alloc::{collections::btree_map::BTreeMap, vec::Vec};
-use serde::{Deserialize, Serialize};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{err, Context, Error, Signal};
+use crate::{err, Context, Error, Signal, SignalHook, SignalName, SignalValue};
 
 mod battle;
 mod card;
@@ -24,6 +27,34 @@ pub const COUNTERPARTY_RUNTIME_ID: u16 = 4;
 
 #[macro_export]
 macro_rules! push_log {
+    ($ctx:expr, { name: $name:ident, event: $event:expr, value: $val:expr, recipient: $recp:expr, }) => {
+        #[cfg(feature = "log")]
+        $ctx.log($crate::Log {
+            name: $crate::LogName::$name,
+            attribute: None,
+            value: Some($val as u16),
+            recipient: Some($recp),
+            event: Some($event),
+            operation: Default::default(),
+            cause: Default::default(),
+        });
+        #[cfg(not(feature = "log"))]
+        $ctx.log();
+    };
+    ($ctx:expr, { name: $name:ident, event: $event:expr, recipient: $recp:expr, }) => {
+        #[cfg(feature = "log")]
+        $ctx.log($crate::Log {
+            name: $crate::LogName::$name,
+            attribute: None,
+            value: None,
+            recipient: Some($recp),
+            event: Some($event),
+            operation: Default::default(),
+            cause: Default::default(),
+        });
+        #[cfg(not(feature = "log"))]
+        $ctx.log();
+    };
     ($ctx:expr, { name: $name:ident, attribute: $attr:expr, value: $val:expr, recipient: $recp:expr, }) => {
         #[cfg(feature = "log")]
         $ctx.log($crate::Log {
@@ -31,6 +62,9 @@ macro_rules! push_log {
             attribute: Some($attr),
             value: Some($val as u16),
             recipient: Some($recp),
+            event: None,
+            operation: Default::default(),
+            cause: Default::default(),
         });
         #[cfg(not(feature = "log"))]
         $ctx.log();
@@ -42,6 +76,9 @@ macro_rules! push_log {
             attribute: None,
             value: None,
             recipient: Some($recp),
+            event: None,
+            operation: Default::default(),
+            cause: Default::default(),
         });
         #[cfg(not(feature = "log"))]
         $ctx.log();
@@ -53,6 +90,9 @@ macro_rules! push_log {
             attribute: Some($attr),
             value: None,
             recipient: Some($recp),
+            event: None,
+            operation: Default::default(),
+            cause: Default::default(),
         });
         #[cfg(not(feature = "log"))]
         $ctx.log();
@@ -64,6 +104,9 @@ macro_rules! push_log {
             attribute: None,
             value: Some($val as u16),
             recipient: Some($recp),
+            event: None,
+            operation: Default::default(),
+            cause: Default::default(),
         });
         #[cfg(not(feature = "log"))]
         $ctx.log();
@@ -73,47 +116,295 @@ macro_rules! push_log {
 #[macro_export]
 macro_rules! iter_stats {
     ($ctx:expr, $member:ident, $change:expr) => {
-        $ctx.statistics.$member += $change as u16;
+        $ctx.statistics.$member = $ctx.statistics.$member.saturating_add($change as u16);
     };
 }
 
-#[derive(Default, Serialize, Deserialize)]
-#[serde(transparent)]
+/// Hp changed by a `ChangeHp` or `ChangeRealHp` signal
+#[derive(Default)]
+pub struct HpChange {
+    pub lost: u16,
+    pub blocked: u16,
+    pub healed: u16,
+}
+
+impl HpChange {
+    /// Log the damage or healing that `target` took from the source of `signal`
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+    pub fn log(&self, signal: &Signal, target: u16, ctx: &mut Context) {
+        if self.lost > 0 || self.blocked > 0 {
+            push_log!(ctx, {
+                name: Damage,
+                event: crate::LogEvent::Damage {
+                    source: signal.source_runtime_id,
+                    target,
+                    amount: self.lost,
+                    blocked: self.blocked,
+                },
+                value: self.lost,
+                recipient: target,
+            });
+        }
+        if self.healed > 0 {
+            push_log!(ctx, {
+                name: Heal,
+                event: crate::LogEvent::Heal {
+                    source: signal.source_runtime_id,
+                    target,
+                    amount: self.healed,
+                },
+                value: self.healed,
+                recipient: target,
+            });
+        }
+    }
+}
+
+/// Apply the hp change of `signal` to an entity, damage of `ChangeHp` is absorbed by block and
+/// then shield while `ChangeRealHp` ignores both, returns `None` if the value is neither
+/// positive nor negative
+pub fn change_hp(
+    signal: &Signal,
+    hp: &mut u16,
+    max_hp: u16,
+    block: &mut u16,
+    shield: &mut u16,
+) -> Option<HpChange> {
+    match signal.value {
+        SignalValue::Negative(damage) => {
+            let mut blocked = 0;
+            if signal.name == SignalName::ChangeHp {
+                for guard in [block, shield] {
+                    let absorbed = (damage - blocked).min(*guard);
+                    *guard -= absorbed;
+                    blocked += absorbed;
+                }
+            }
+            let lost = (damage - blocked).min(*hp);
+            *hp -= lost;
+            Some(HpChange {
+                lost,
+                blocked,
+                ..Default::default()
+            })
+        }
+        SignalValue::Positive(healing) => {
+            let healed = healing.min(max_hp.saturating_sub(*hp));
+            *hp += healed;
+            Some(HpChange {
+                healed,
+                ..Default::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Runtimes of the game keyed by runtime id, along with indexes of who runs which signals
+///
+/// Each runtime lives in a slot, running it takes the runtime out of the slot and puts it back
+/// afterwards, so that it can read the rest of the pool through the context while the map and
+/// indexes are left untouched
+#[derive(Default)]
 pub struct RuntimePool {
-    pool: BTreeMap<u16, Runtime>,
+    slots: BTreeMap<u16, Option<Runtime>>,
+    // Effects, battle and player running each signal name, keyed by dispatch rank and id
+    observers: BTreeMap<SignalName, BTreeSet<(u8, u16)>>,
+    effects: BTreeSet<u16>,
+    // Effects implementing transforms and each `SignalHook`, traps springing count as `After`
+    transformers: BTreeSet<u16>,
+    hooks: [BTreeSet<u16>; 2],
+}
+
+impl Serialize for RuntimePool {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Archived as the map of runtimes, runtimes are never detached while archiving
+        let pool = self
+            .slots
+            .iter()
+            .filter_map(|(runtime_id, slot)| Some((runtime_id, slot.as_ref()?)))
+            .collect::<BTreeMap<_, _>>();
+        pool.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RuntimePool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pool = BTreeMap::<u16, Runtime>::deserialize(deserializer)?;
+        let mut runtimes = RuntimePool::default();
+        pool.into_values()
+            .for_each(|runtime| runtimes.insert(runtime));
+        Ok(runtimes)
+    }
 }
 
 impl RuntimePool {
     pub fn insert(&mut self, runtime: Runtime) {
-        self.pool.insert(runtime.runtime_id(), runtime);
+        let runtime_id = runtime.runtime_id();
+        if !runtime.runtime_type().addressed() {
+            let rank = runtime.runtime_type().dispatch_rank();
+            runtime.subscriptions().iter().for_each(|name| {
+                self.observers
+                    .entry(*name)
+                    .or_default()
+                    .insert((rank, runtime_id));
+            });
+        }
+        if let Runtime::Effect(effect) = &runtime {
+            let flags = effect.flags();
+            self.effects.insert(runtime_id);
+            if flags.transform {
+                self.transformers.insert(runtime_id);
+            }
+            if flags.before {
+                self.hooks[SignalHook::Before as usize].insert(runtime_id);
+            }
+            if flags.after || flags.spring {
+                self.hooks[SignalHook::After as usize].insert(runtime_id);
+            }
+        }
+        self.slots.insert(runtime_id, Some(runtime));
     }
 
     pub fn get(&self, id: &u16) -> Result<&Runtime, Error> {
-        self.pool.get(id).ok_or(err!(RuntimeNotSet(*id)))
+        self.slots
+            .get(id)
+            .and_then(Option::as_ref)
+            .ok_or(err!(RuntimeNotSet(*id)))
+    }
+
+    pub fn get_mut(&mut self, id: &u16) -> Result<&mut Runtime, Error> {
+        self.slots
+            .get_mut(id)
+            .and_then(Option::as_mut)
+            .ok_or(err!(RuntimeNotSet(*id)))
     }
 
     pub fn remove(&mut self, id: &u16) -> Result<Runtime, Error> {
-        self.pool.remove(id).ok_or(err!(RuntimeNotSet(*id)))
+        self.release(id).ok_or(err!(RuntimeNotSet(*id)))
+    }
+
+    /// Take the runtime out of its slot to run it, which `get` doesn't find until `attach`
+    pub fn detach(&mut self, id: &u16) -> Option<Runtime> {
+        self.slots.get_mut(id)?.take()
+    }
+
+    /// Put a detached runtime back into its slot
+    pub fn attach(&mut self, runtime: Runtime) {
+        match self.slots.get_mut(&runtime.runtime_id()) {
+            Some(slot) => *slot = Some(runtime),
+            None => self.insert(runtime),
+        }
+    }
+
+    /// Drop the slot and subscriptions of the runtime, returning it unless it is detached
+    pub fn release(&mut self, id: &u16) -> Option<Runtime> {
+        let runtime = self.slots.remove(id)?;
+        self.effects.remove(id);
+        self.transformers.remove(id);
+        self.hooks.iter_mut().for_each(|hooks| {
+            hooks.remove(id);
+        });
+        match &runtime {
+            Some(runtime) => runtime.subscriptions().iter().for_each(|name| {
+                if let Some(observers) = self.observers.get_mut(name) {
+                    observers.retain(|(_, runtime_id)| runtime_id != id);
+                }
+            }),
+            // Subscriptions of a detached runtime are unknown here
+            None => self.observers.values_mut().for_each(|observers| {
+                observers.retain(|(_, runtime_id)| runtime_id != id);
+            }),
+        }
+        runtime
     }
 
     #[cfg(feature = "debug")]
     pub fn iter(&self) -> impl Iterator<Item = (&u16, &Runtime)> {
-        self.pool.iter()
+        self.slots
+            .iter()
+            .filter_map(|(runtime_id, slot)| Some((runtime_id, slot.as_ref()?)))
     }
 
     pub fn collect_runtime_ids(&self, runtime_type: RuntimeType) -> Vec<u16> {
-        self.pool
+        self.slots
             .iter()
-            .filter(|(_, v)| v.runtime_type() == runtime_type)
+            .filter(|(_, v)| v.as_ref().is_some_and(|v| v.runtime_type() == runtime_type))
             .map(|(k, _)| *k)
             .collect()
     }
 
-    pub fn collect_runtimes(&self, runtime_type: RuntimeType) -> Vec<&Runtime> {
-        self.pool
+    /// Ids of all effects in runtime id order, without scanning the pool
+    pub fn effect_ids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.effects.iter().copied()
+    }
+
+    /// Ids of effects implementing transforms in runtime id order
+    pub fn transformer_ids(&self) -> impl Iterator<Item = u16> + '_ {
+        self.transformers.iter().copied()
+    }
+
+    /// Ids of effects implementing the hook in runtime id order, along with traps for `After`
+    pub fn hook_ids(&self, hook: SignalHook) -> impl Iterator<Item = u16> + '_ {
+        self.hooks[hook as usize].iter().copied()
+    }
+
+    /// Fill `subscribers` with ids of runtimes that run `signal`, in the order of cards,
+    /// enemies, effects, battle and player
+    ///
+    /// Cards and enemies are only looked up among the source and targets of `signal`, while
+    /// the rest are read from the subscriptions of the signal name
+    pub fn collect_subscribers(&self, signal: &Signal, subscribers: &mut Vec<u16>) {
+        let addressed = signal
+            .target_runtime_ids
             .iter()
-            .filter(|(_, v)| v.runtime_type() == runtime_type)
-            .map(|(_, v)| v)
+            .chain(core::iter::once(&signal.source_runtime_id));
+        for runtime_type in [RuntimeType::Card, RuntimeType::Enemy] {
+            let start = subscribers.len();
+            subscribers.extend(addressed.clone().copied().filter(|runtime_id| {
+                self.get(runtime_id).is_ok_and(|runtime| {
+                    runtime.runtime_type() == runtime_type
+                        && runtime.subscriptions().contains(&signal.name)
+                })
+            }));
+            subscribers[start..].sort_unstable();
+        }
+        subscribers.dedup();
+        if let Some(observers) = self.observers.get(&signal.name) {
+            subscribers.extend(observers.iter().map(|(_, runtime_id)| *runtime_id));
+        }
+    }
+
+    /// Cards and effects act on behalf of their parents, others own themselves
+    pub fn owner_runtime_id(&self, runtime_id: u16) -> u16 {
+        match self.get(&runtime_id) {
+            Ok(Runtime::Card(card)) => card.parent_runtime_id,
+            Ok(Runtime::Effect(effect)) => effect.parent_runtime_id,
+            _ => runtime_id,
+        }
+    }
+
+    /// The player and enemies are opponents of each other, while the battle is neutral
+    pub fn opponents(&self, left: u16, right: u16) -> bool {
+        let runtime_type = |runtime_id| self.get(&runtime_id).ok().map(Runtime::runtime_type);
+        matches!(
+            (runtime_type(left), runtime_type(right)),
+            (Some(RuntimeType::Player), Some(RuntimeType::Enemy))
+                | (Some(RuntimeType::Enemy), Some(RuntimeType::Player))
+        )
+    }
+
+    pub fn collect_runtimes(&self, runtime_type: RuntimeType) -> Vec<&Runtime> {
+        self.slots
+            .values()
+            .filter_map(Option::as_ref)
+            .filter(|v| v.runtime_type() == runtime_type)
             .collect()
     }
 }
@@ -128,6 +419,24 @@ pub enum RuntimeType {
     PveBattle,
 }
 
+impl RuntimeType {
+    /// Signals are run by cards, enemies, effects, battle and at last the player
+    pub fn dispatch_rank(&self) -> u8 {
+        match self {
+            Self::Card => 0,
+            Self::Enemy => 1,
+            Self::Effect => 2,
+            Self::PveBattle => 3,
+            Self::Player => 4,
+        }
+    }
+
+    /// Cards and enemies only run signals addressed to or sent by themselves
+    pub fn addressed(&self) -> bool {
+        matches!(self, Self::Card | Self::Enemy)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub enum Runtime {
@@ -159,6 +468,13 @@ impl Runtime {
         }
     }
 
+    pub fn card(&self) -> Result<&CardRuntime, Error> {
+        match self {
+            Runtime::Card(card) => Ok(card),
+            _ => Err(err!(InvalidRuntimeType)),
+        }
+    }
+
     pub fn player(&self) -> Result<&PlayerRuntime, Error> {
         match self {
             Runtime::Player(player) => Ok(player),
@@ -173,6 +489,33 @@ impl Runtime {
         }
     }
 
+    pub fn effect(&self) -> Result<&EffectRuntime, Error> {
+        match self {
+            Runtime::Effect(effect) => Ok(effect),
+            _ => Err(err!(InvalidRuntimeType)),
+        }
+    }
+
+    /// Effects attached to the player, an enemy or the battle
+    pub fn active_effects_mut(&mut self) -> Result<&mut Vec<u16>, Error> {
+        match self {
+            Runtime::Player(player) => Ok(&mut player.active_effects),
+            Runtime::Enemy(enemy) => Ok(&mut enemy.active_effects),
+            Runtime::PveBattle(battle) => Ok(&mut battle.active_effects),
+            _ => Err(err!(InvalidRuntimeType)),
+        }
+    }
+
+    /// Current hp in percent of the max hp, only for the player and enemies
+    pub fn hp_percent(&self) -> Option<u8> {
+        let (hp, max_hp) = match self {
+            Runtime::Player(player) => (player.hp, player.raw.hp),
+            Runtime::Enemy(enemy) => (enemy.hp, enemy.raw.hp),
+            _ => return None,
+        };
+        Some((hp as u32 * 100 / max_hp.max(1) as u32).min(100) as u8)
+    }
+
     #[cfg(feature = "debug")]
     pub fn pve_battle(&self) -> Result<&PveBattleRuntime, Error> {
         match self {
@@ -180,6 +523,19 @@ impl Runtime {
             _ => Err(err!(InvalidRuntimeType)),
         }
     }
+
+    /// Signal names the runtime runs, see `RuntimeType::addressed` for who filters them by
+    /// source and targets
+    pub fn subscriptions(&self) -> &'static [SignalName] {
+        match self {
+            Self::Card(_) => CardRuntime::SUBSCRIPTIONS,
+            Self::Enemy(_) => EnemyRuntime::SUBSCRIPTIONS,
+            Self::Player(_) => PlayerRuntime::SUBSCRIPTIONS,
+            Self::Effect(_) => EffectRuntime::SUBSCRIPTIONS,
+            Self::PveBattle(_) => PveBattleRuntime::SUBSCRIPTIONS,
+        }
+    }
+
     pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
         match self {
             Self::Effect(v) => v.run(signal, ctx),
@@ -196,4 +552,23 @@ impl Runtime {
             _ => Err(err!(TransformOnlyForEffect)),
         }
     }
+
+    pub fn hook(
+        &self,
+        hook: SignalHook,
+        signal: &Signal,
+        ctx: &mut Context,
+    ) -> Result<Vec<Signal>, Error> {
+        match self {
+            Self::Effect(v) => v.hook(hook, signal, ctx),
+            _ => Err(err!(TransformOnlyForEffect)),
+        }
+    }
+
+    pub fn spring(&self, signal: &Signal, ctx: &mut Context) -> Result<Option<Vec<Signal>>, Error> {
+        match self {
+            Self::Effect(v) => v.spring(signal, ctx),
+            _ => Err(err!(TransformOnlyForEffect)),
+        }
+    }
 }
@@ -1,11 +1,29 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use common::player::Player;
 use serde::{Deserialize, Serialize};
 
-use crate::{Context, Error, Signal};
+use crate::{
+    change_hp, err, iter_stats, push_log, Context, Error, Signal, SignalName, SignalValue,
+};
 
-#[cfg(feature = "log")]
-use crate::log::CardMovement;
+// Log each of `cards` moving between the piles of player, which only exists for logs
+macro_rules! log_card_moves {
+    ($ctx:expr, $recipient:expr, $cards:expr, $from:ident => $to:ident) => {
+        #[cfg(feature = "log")]
+        $cards.iter().for_each(|card| {
+            push_log!($ctx, {
+                name: CardMove,
+                event: crate::LogEvent::CardMove {
+                    card: *card,
+                    from: crate::CardPile::$from,
+                    to: crate::CardPile::$to,
+                },
+                value: *card,
+                recipient: $recipient,
+            });
+        });
+    };
+}
 
 // Runtime state for a player that tracks attributes, card collections,
 // and effects during gameplay. Manages core game mechanics like deck/hand
@@ -32,7 +50,159 @@ pub struct PlayerRuntime {
 }
 
 impl PlayerRuntime {
-    pub fn run(&mut self, _signal: &Signal, _ctx: &mut Context) -> Result<(), Error> {
+    pub const SUBSCRIPTIONS: &'static [SignalName] = &[
+        SignalName::PlayerTurn,
+        SignalName::EnemyTurn,
+        SignalName::BattleOver,
+        SignalName::SpellCard,
+        SignalName::ChangeHp,
+        SignalName::ChangeRealHp,
+        SignalName::ChangeEnergy,
+    ];
+
+    pub fn new(player: Player, runtime_id: u16, deck_cards: Vec<u16>) -> Self {
+        Self {
+            runtime_id,
+            energy: player.energy,
+            hp: player.hp,
+            attack: player.attack,
+            defense: player.defense,
+            spirit: player.spirit,
+            block: 0,
+            shield: 0,
+            equipment_cards: Vec::new(),
+            sorcery_cards: Vec::new(),
+            handhold_cards: Vec::new(),
+            deck_cards,
+            grave_cards: Vec::new(),
+            exile_cards: Vec::new(),
+            active_effects: Vec::new(),
+            raw: player,
+        }
+    }
+
+    pub fn run(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        match signal.name {
+            SignalName::PlayerTurn => self.start_turn(ctx),
+            // Cards left in hand are discarded once the player ends the turn
+            SignalName::EnemyTurn => {
+                log_card_moves!(ctx, self.runtime_id, self.handhold_cards, Hand => Grave);
+                self.grave_cards.append(&mut self.handhold_cards);
+                Ok(())
+            }
+            SignalName::BattleOver => {
+                self.reset_cards(ctx);
+                Ok(())
+            }
+            _ if !signal.is_target(self.runtime_id) => Ok(()),
+            SignalName::SpellCard => self.spell_card(signal, ctx),
+            SignalName::ChangeHp | SignalName::ChangeRealHp => {
+                let change = change_hp(
+                    signal,
+                    &mut self.hp,
+                    self.raw.hp,
+                    &mut self.block,
+                    &mut self.shield,
+                )
+                .ok_or(err!(PlayerInvalidSignalValue(signal.name)))?;
+                iter_stats!(ctx, sufferred_damage, change.lost);
+                iter_stats!(ctx, blocked_damage, change.blocked);
+                iter_stats!(ctx, healed_hp, change.healed);
+                change.log(signal, self.runtime_id, ctx);
+                Ok(())
+            }
+            SignalName::ChangeEnergy => self.change_energy(signal),
+            _ => Ok(()),
+        }
+    }
+
+    // Block is cleared and energy is refilled, then cards are drawn up to the initial handhold
+    fn start_turn(&mut self, ctx: &mut Context) -> Result<(), Error> {
+        self.block = 0;
+        self.energy = self.raw.energy;
+        self.draw_cards(self.raw.initial_handhold_capacity, ctx);
+        Ok(())
+    }
+
+    /// Draw random cards from deck, which is refilled by the grave once it runs out
+    pub fn draw_cards(&mut self, count: u8, ctx: &mut Context) {
+        for _ in 0..count {
+            if self.handhold_cards.len() >= self.raw.max_handhold_capacity as usize {
+                break;
+            }
+            if self.deck_cards.is_empty() {
+                log_card_moves!(ctx, self.runtime_id, self.grave_cards, Grave => Deck);
+                self.deck_cards.append(&mut self.grave_cards);
+            }
+            if self.deck_cards.is_empty() {
+                break;
+            }
+            let index = ctx.rng.next_u64() % self.deck_cards.len() as u64;
+            let card_runtime_id = self.deck_cards.remove(index as usize);
+            log_card_moves!(ctx, self.runtime_id, [card_runtime_id], Deck => Hand);
+            self.handhold_cards.push(card_runtime_id);
+        }
+    }
+
+    // Pay the cost of a card in hand and put it into grave or exile, then the card is asked to
+    // take effect with the target chosen by the operation
+    fn spell_card(&mut self, signal: &Signal, ctx: &mut Context) -> Result<(), Error> {
+        if !ctx.battle_running() {
+            return Err(err!(BattleNotStarted));
+        }
+        let card_runtime_id = signal.source_runtime_id;
+        let position = self
+            .handhold_cards
+            .iter()
+            .position(|runtime_id| *runtime_id == card_runtime_id)
+            .ok_or(err!(PlayerCardNotFound))?;
+        let card = ctx.runtimes.get(&card_runtime_id)?.card()?;
+        let (cost, exile) = (card.cost, card.exile);
+        if cost > self.energy {
+            return Err(err!(PlayerEnergyInsufficient));
+        }
+        self.energy -= cost;
+        self.handhold_cards.remove(position);
+        if exile {
+            log_card_moves!(ctx, self.runtime_id, [card_runtime_id], Hand => Exile);
+            self.exile_cards.push(card_runtime_id);
+        } else {
+            log_card_moves!(ctx, self.runtime_id, [card_runtime_id], Hand => Grave);
+            self.grave_cards.push(card_runtime_id);
+        }
+        ctx.signal(Signal {
+            name: SignalName::SpellCard,
+            value: signal.value.clone(),
+            transformed: false,
+            source_runtime_id: self.runtime_id,
+            target_runtime_ids: vec![card_runtime_id],
+        });
+        Ok(())
+    }
+
+    fn change_energy(&mut self, signal: &Signal) -> Result<(), Error> {
+        match signal.value {
+            SignalValue::Positive(energy) => {
+                self.energy = self.energy.saturating_add(energy.min(u8::MAX as u16) as u8);
+            }
+            SignalValue::Negative(energy) => {
+                self.energy = self.energy.saturating_sub(energy.min(u8::MAX as u16) as u8);
+            }
+            _ => return Err(err!(PlayerInvalidSignalValue(signal.name))),
+        }
         Ok(())
     }
+
+    // Every card returns to deck after battle, along with the battle states
+    fn reset_cards(&mut self, ctx: &mut Context) {
+        log_card_moves!(ctx, self.runtime_id, self.handhold_cards, Hand => Deck);
+        log_card_moves!(ctx, self.runtime_id, self.grave_cards, Grave => Deck);
+        log_card_moves!(ctx, self.runtime_id, self.exile_cards, Exile => Deck);
+        self.deck_cards.append(&mut self.handhold_cards);
+        self.deck_cards.append(&mut self.grave_cards);
+        self.deck_cards.append(&mut self.exile_cards);
+        self.energy = self.raw.energy;
+        self.block = 0;
+        self.shield = 0;
+    }
 }
@@ -1,21 +1,24 @@
 use alloc::{collections::BTreeMap, vec, vec::Vec};
 use common::{
-    card::Card, enemy::EnemyLevel, enum_with_display, hardcoded::DNA, player::roulette_player,
+    card::{decode_dna, Card, CardTarget},
+    enemy::{roulette_enemy, EnemyLevel},
+    enum_with_display,
+    hardcoded::{ARCHIVE_VERSION, DNA},
+    operation::GameOperation,
+    player::roulette_player,
 };
-use database::PLAYER_POOL;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    err, iter_stats, push_log, Context, Error, GameStatistics, RuntimeType, Signal, SignalName,
-    SignalValue, BATTLE_RUNTIME_ID, PLAYER_RUNTIME_ID, SYSTEM_RUNTIME_ID,
+    card_pool, enemy_pool, err, iter_stats, player_pool, push_log, CardRuntime, Context,
+    EffectRuntime, EnemyRuntime, Error, GameStatistics, GameView, LootCardView, PlayerRuntime,
+    PveBattleRuntime, Runtime, RuntimeType, Signal, SignalHook, SignalLimits, SignalName,
+    SignalPriority, SignalValue, BATTLE_RUNTIME_ID, PLAYER_RUNTIME_ID, SYSTEM_RUNTIME_ID,
 };
 
-#[cfg(feature = "replay")]
-use common::operation::GameOperation;
-
 enum_with_display!(
-    #[derive(Clone, Copy)]
-    #[cfg_attr(feature = "log", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    #[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
     pub enum CardSelectionSource {
         NoSelection,
         Deck,
@@ -26,14 +29,38 @@ enum_with_display!(
     }
 );
 
-#[derive(Clone, Copy)]
-#[cfg_attr(feature = "log", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
 pub struct CardSelection {
     pub source: CardSelectionSource,
     pub source_runtime_id: u16,
     pub count: usize,
 }
 
+/// Pending selection described by its candidates, `SelectCard` with any of them up to
+/// `max_count` distinct cards is legal, including none of them
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
+pub struct LegalSelection {
+    pub source: CardSelectionSource,
+    pub candidates: Vec<u16>,
+    pub max_count: usize,
+}
+
+/// Operations which pass the checks of current state, the selection is described instead of
+/// enumerated since its combinations grow exponentially with the candidates
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LegalOperations {
+    pub operations: Vec<GameOperation>,
+    pub selection: Option<LegalSelection>,
+}
+
+impl LegalOperations {
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty() && self.selection.is_none()
+    }
+}
+
 impl Default for CardSelection {
     fn default() -> Self {
         Self {
@@ -97,9 +124,28 @@ impl CardSelection {
     }
 }
 
+/// Parameters decided by the game session rather than by operations, which `StartGame` and
+/// `StartBattle` are applied with
+#[derive(Clone, Default)]
+pub struct GameSetup {
+    pub player_level: u8,
+    pub action_point: u16,
+    pub gameplay_cards: Vec<DNA>,
+    pub version: u8,
+}
+
+// Archive of the runtime taken right before the operation at `operation_count`
+#[cfg(feature = "replay")]
+struct Checkpoint {
+    operation_count: usize,
+    archive: Vec<u8>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PveSystemRuntime {
+    archive_version: u8,
     ctx: Context,
+    version: u8,
     game_over: bool,
     gold: u16,
     action_point: u16,
@@ -112,44 +158,153 @@ pub struct PveSystemRuntime {
     #[serde(skip)]
     #[serde(default)]
     _loot_count: usize,
+    #[serde(skip)]
+    #[serde(default)]
+    setup: GameSetup,
+    #[serde(skip)]
+    #[serde(default)]
+    subscribers: Vec<u16>,
     #[cfg(feature = "replay")]
     #[serde(skip)]
     #[serde(default)]
     operations: Vec<GameOperation>,
+    #[cfg(feature = "replay")]
+    #[serde(skip)]
+    #[serde(default)]
+    checkpoints: Vec<Checkpoint>,
+    #[cfg(feature = "replay")]
+    #[serde(skip)]
+    #[serde(default)]
+    undone_operations: Vec<GameOperation>,
 }
 
 impl PveSystemRuntime {
     pub fn new(seed: u64) -> Result<Self, Error> {
         Ok(Self {
+            archive_version: ARCHIVE_VERSION,
             ctx: Context::new(seed),
+            version: 0,
             game_over: false,
             card_selection: CardSelection::default(),
             gold: 0,
             _loot_cards: BTreeMap::new(),
             _loot_count: 0,
             action_point: 0,
+            setup: GameSetup::default(),
+            subscribers: vec![],
             #[cfg(feature = "replay")]
             operations: vec![],
+            #[cfg(feature = "replay")]
+            checkpoints: vec![],
+            #[cfg(feature = "replay")]
+            undone_operations: vec![],
         })
     }
 
+    /// Re-execute `operations` of a new game from `seed`, stopping at the first failure
+    pub fn replay(
+        seed: u64,
+        player_level: u8,
+        action_point: u16,
+        gameplay_cards: Vec<DNA>,
+        version: u8,
+        operations: &[GameOperation],
+    ) -> Result<Self, Error> {
+        let mut game = Self::new(seed)?;
+        game.set_setup(GameSetup {
+            player_level,
+            action_point,
+            gameplay_cards,
+            version,
+        });
+        operations
+            .iter()
+            .try_for_each(|operation| game.apply_operation(operation))?;
+        Ok(game)
+    }
+
+    /// Restore the runtime from archive bytes, archives made by other layout versions are
+    /// refused since they can't be read correctly
+    pub fn load(archive: &[u8]) -> Result<Self, Error> {
+        let runtime: Self = serde_molecule::from_slice(archive, false)
+            .map_err(|_| err!(SystemArchiveIncompatible))?;
+        if runtime.archive_version != ARCHIVE_VERSION {
+            return Err(err!(SystemArchiveIncompatible));
+        }
+        Ok(runtime)
+    }
+
+    /// Setup is not archived, so it must be set again after loading an archive
+    pub fn set_setup(&mut self, setup: GameSetup) {
+        self.setup = setup;
+    }
+
+    /// Limits are not archived either, the default ones are what the contract runs with
+    pub fn set_signal_limits(&mut self, signal_limits: SignalLimits) {
+        self.ctx.signal_limits = signal_limits;
+    }
+
+    /// The only dispatcher from `GameOperation` to operation methods, which keeps the contract
+    /// and clients replaying in the same way
+    pub fn apply_operation(&mut self, operation: &GameOperation) -> Result<(), Error> {
+        match operation.clone() {
+            GameOperation::StartGame => self.start_game(
+                self.setup.player_level,
+                self.setup.action_point,
+                self.setup.gameplay_cards.clone(),
+                self.setup.version,
+            ),
+            GameOperation::StartBattle(enemy_level) => {
+                self.start_battle(enemy_level, self.setup.version)
+            }
+            GameOperation::RoundOver => self.round_over(),
+            GameOperation::SpellCard(card_runtime_id, target_runtime_id) => {
+                self.spell_card(card_runtime_id, target_runtime_id)
+            }
+            GameOperation::SelectCard(card_runtime_id_set) => self.select_card(card_runtime_id_set),
+            GameOperation::HealHp => self.heal_hp(),
+            GameOperation::DestroyCard => self.destroy_card(),
+        }
+    }
+
     pub fn game_over(&self) -> bool {
         self.game_over
     }
 
+    /// Battle states and pending selections are not archived, so archiving only keeps
+    /// the whole game while neither of them exists
+    pub fn archivable(&self) -> bool {
+        !self.ctx.battle_running() && !self.card_selection.wait_selection(true)
+    }
+
     pub fn start_game(
         &mut self,
         player_level: u8,
         player_action_point: u16,
         gameplay_cards: Vec<DNA>,
+        version: u8,
+    ) -> Result<(), Error> {
+        self.operate(
+            || GameOperation::StartGame,
+            |game| game.run_start_game(player_level, player_action_point, gameplay_cards, version),
+        )
+    }
+
+    fn run_start_game(
+        &mut self,
+        player_level: u8,
+        player_action_point: u16,
+        gameplay_cards: Vec<DNA>,
+        version: u8,
     ) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations.push(GameOperation::StartGame);
         if self.card_selection.wait_selection(false) {
             return Err(err!(SystemCardSelectionWait));
         }
-        let player = roulette_player(PLAYER_POOL, player_level, self.ctx.rng.next_u64())
+        let seed = self.ctx.rng.next_u64();
+        let player = player_pool()
+            .and_then(|player_pool| roulette_player(player_pool, player_level, seed))
             .ok_or(err!(PlayerLevelNotFound(player_level)))?;
+        self.version = version;
         self.run_signal(Signal {
             name: SignalName::InitPlayer,
             value: SignalValue::Player(player, player_action_point, gameplay_cards),
@@ -168,9 +323,13 @@ impl PveSystemRuntime {
     }
 
     pub fn start_battle(&mut self, enemy_level: EnemyLevel, version: u8) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations
-            .push(GameOperation::StartBattle(enemy_level));
+        self.operate(
+            || GameOperation::StartBattle(enemy_level),
+            |game| game.run_start_battle(enemy_level, version),
+        )
+    }
+
+    fn run_start_battle(&mut self, enemy_level: EnemyLevel, version: u8) -> Result<(), Error> {
         if self.card_selection.wait_selection(false) {
             return Err(err!(SystemCardSelectionWait));
         }
@@ -191,17 +350,24 @@ impl PveSystemRuntime {
     }
 
     pub fn round_over(&mut self) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations.push(GameOperation::RoundOver);
+        self.operate(|| GameOperation::RoundOver, Self::run_round_over)
+    }
+
+    fn run_round_over(&mut self) -> Result<(), Error> {
         if self.card_selection.wait_selection(false) {
             return Err(err!(SystemCardSelectionWait));
         }
+        if !self.ctx.battle_running() {
+            return Err(err!(BattleNotStarted));
+        }
+        let mut target_runtime_ids = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Enemy);
+        target_runtime_ids.push(BATTLE_RUNTIME_ID);
         self.run_signal(Signal {
             name: SignalName::EnemyTurn,
             value: SignalValue::Empty,
             transformed: false,
             source_runtime_id: SYSTEM_RUNTIME_ID,
-            target_runtime_ids: vec![BATTLE_RUNTIME_ID],
+            target_runtime_ids,
         })
     }
 
@@ -210,9 +376,17 @@ impl PveSystemRuntime {
         card_runtime_id: u16,
         target_runtime_id: Option<u16>,
     ) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations
-            .push(GameOperation::SpellCard(card_runtime_id, target_runtime_id));
+        self.operate(
+            || GameOperation::SpellCard(card_runtime_id, target_runtime_id),
+            |game| game.run_spell_card(card_runtime_id, target_runtime_id),
+        )
+    }
+
+    fn run_spell_card(
+        &mut self,
+        card_runtime_id: u16,
+        target_runtime_id: Option<u16>,
+    ) -> Result<(), Error> {
         if self.card_selection.wait_selection(false) {
             return Err(err!(SystemCardSelectionWait));
         }
@@ -237,9 +411,13 @@ impl PveSystemRuntime {
     }
 
     pub fn select_card(&mut self, runtime_ids: Vec<u16>) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations
-            .push(GameOperation::SelectCard(runtime_ids.clone()));
+        self.operate(
+            || GameOperation::SelectCard(runtime_ids.clone()),
+            |game| game.run_select_card(runtime_ids),
+        )
+    }
+
+    fn run_select_card(&mut self, runtime_ids: Vec<u16>) -> Result<(), Error> {
         if !self.card_selection.wait_selection(true) {
             return Err(err!(SystemCardSelectionNotWait));
         }
@@ -252,8 +430,10 @@ impl PveSystemRuntime {
     }
 
     pub fn heal_hp(&mut self) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations.push(GameOperation::HealHp);
+        self.operate(|| GameOperation::HealHp, Self::run_heal_hp)
+    }
+
+    fn run_heal_hp(&mut self) -> Result<(), Error> {
         if self.ctx.battle_running() {
             return Err(err!(SystemBattleInProgress));
         }
@@ -282,8 +462,10 @@ impl PveSystemRuntime {
     }
 
     pub fn destroy_card(&mut self) -> Result<(), Error> {
-        #[cfg(feature = "replay")]
-        self.operations.push(GameOperation::DestroyCard);
+        self.operate(|| GameOperation::DestroyCard, Self::run_destroy_card)
+    }
+
+    fn run_destroy_card(&mut self) -> Result<(), Error> {
         if self.ctx.battle_running() {
             return Err(err!(SystemBattleInProgress));
         }
@@ -300,7 +482,7 @@ impl PveSystemRuntime {
         self.card_selection = CardSelection::from_signal(&signal)?;
         push_log!(self.ctx, {
             name: SelectCards,
-            attribute: serde_json::to_string(&self.card_selection).unwrap(),
+            event: crate::LogEvent::SelectCards(self.card_selection),
             value: self.card_selection.source_runtime_id,
             recipient: PLAYER_RUNTIME_ID,
         });
@@ -335,6 +517,92 @@ impl PveSystemRuntime {
         &self.ctx.statistics
     }
 
+    pub fn view(&self) -> GameView {
+        GameView {
+            version: self.version,
+            game_over: self.game_over,
+            gold: self.gold,
+            action_point: self.action_point,
+            statistics: self.ctx.statistics.clone(),
+            player: GameView::player(&self.ctx),
+            battle: GameView::battle(&self.ctx),
+            card_selection: self.card_selection,
+            loot_cards: self
+                ._loot_cards
+                .iter()
+                .map(|(runtime_id, card)| LootCardView {
+                    runtime_id: *runtime_id,
+                    card: card.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Every operation that passes the checks of current state, so that clients and bots
+    /// don't have to learn from errors
+    pub fn legal_operations(&self) -> LegalOperations {
+        let mut operations = vec![];
+        if self.game_over {
+            return LegalOperations {
+                operations,
+                selection: None,
+            };
+        }
+        let player = self
+            .ctx
+            .runtimes
+            .get(&PLAYER_RUNTIME_ID)
+            .and_then(Runtime::player)
+            .ok();
+        let selection = self
+            .card_selection
+            .wait_selection(true)
+            .then(|| self.legal_selection(player));
+        // Loot selection is the only one that doesn't block other operations
+        let selection_blocked = self.card_selection.wait_selection(false);
+        let Some(player) = player else {
+            if !selection_blocked {
+                operations.push(GameOperation::StartGame);
+            }
+            return LegalOperations {
+                operations,
+                selection,
+            };
+        };
+        if self.ctx.battle_running() {
+            if !selection_blocked {
+                operations.extend(self.playable_spells(player));
+                operations.push(GameOperation::RoundOver);
+            }
+            return LegalOperations {
+                operations,
+                selection,
+            };
+        }
+        if self.action_point >= player.raw.heal_action_point as u16 {
+            operations.push(GameOperation::HealHp);
+        }
+        if !selection_blocked {
+            if self.action_point >= player.raw.discard_action_point as u16 {
+                operations.push(GameOperation::DestroyCard);
+            }
+            [
+                (EnemyLevel::Easy, player.raw.easy_action_point),
+                (EnemyLevel::Normal, player.raw.normal_action_point),
+                (EnemyLevel::Hard, player.raw.hard_action_point),
+            ]
+            .into_iter()
+            .filter(|(_, action_point)| self.action_point >= *action_point as u16)
+            .for_each(|(enemy_level, _)| {
+                operations.push(GameOperation::StartBattle(enemy_level));
+            });
+        }
+        LegalOperations {
+            operations,
+            selection,
+        }
+    }
+
     #[cfg(feature = "log")]
     pub fn logs(&mut self) -> Vec<crate::Log> {
         self.ctx.dump_logs()
@@ -344,30 +612,216 @@ impl PveSystemRuntime {
     pub fn operations(&self) -> &Vec<GameOperation> {
         &self.operations
     }
+
+    /// Take back the last operation, returns false if there is nothing to undo
+    ///
+    /// The state is restored from the latest checkpoint before it and the operations in
+    /// between are replayed, recorded operations are truncated so they stay submittable
+    #[cfg(feature = "replay")]
+    pub fn undo(&mut self) -> Result<bool, Error> {
+        let Some(operation) = self.operations.last().cloned() else {
+            return Ok(false);
+        };
+        self.rewind(self.operations.len() - 1)?;
+        self.undone_operations.push(operation);
+        Ok(true)
+    }
+
+    /// Apply the last undone operation again, returns false if there is nothing to redo
+    #[cfg(feature = "replay")]
+    pub fn redo(&mut self) -> Result<bool, Error> {
+        let Some(operation) = self.undone_operations.pop() else {
+            return Ok(false);
+        };
+        // Applying a new operation drops the redo history, which should stay for redo itself
+        let undone_operations = core::mem::take(&mut self.undone_operations);
+        let result = self.apply_operation(&operation);
+        self.undone_operations = undone_operations;
+        result.map(|_| true)
+    }
+
+    #[cfg(feature = "replay")]
+    pub fn can_undo(&self) -> bool {
+        !self.operations.is_empty()
+    }
+
+    #[cfg(feature = "replay")]
+    pub fn can_redo(&self) -> bool {
+        !self.undone_operations.is_empty()
+    }
 }
 
 impl PveSystemRuntime {
+    // Only succeeded operations are recorded, a failed one is taken back along with whatever it
+    // has changed, since the contract stops replaying at the first failure
+    fn operate(
+        &mut self,
+        _operation: impl FnOnce() -> GameOperation,
+        run: impl FnOnce(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "replay")]
+        let undone_operations = {
+            self.checkpoint();
+            self.operations.push(_operation());
+            core::mem::take(&mut self.undone_operations)
+        };
+        self.ctx.begin_operation();
+        let result = run(self);
+        #[cfg(feature = "replay")]
+        if result.is_err() {
+            self.rewind(self.operations.len() - 1)?;
+            self.undone_operations = undone_operations;
+        }
+        result
+    }
+
+    // Only archivable states can be checkpoints, since battles and selections are not archived
+    #[cfg(feature = "replay")]
+    fn checkpoint(&mut self) {
+        let operation_count = self.operations.len();
+        let recorded = self
+            .checkpoints
+            .last()
+            .is_some_and(|checkpoint| checkpoint.operation_count == operation_count);
+        if recorded || !self.archivable() {
+            return;
+        }
+        if let Ok(archive) = serde_molecule::to_vec(self, false) {
+            self.checkpoints.push(Checkpoint {
+                operation_count,
+                archive,
+            });
+        }
+    }
+
+    // Restore the state right after the first `operation_count` operations
+    #[cfg(feature = "replay")]
+    fn rewind(&mut self, operation_count: usize) -> Result<(), Error> {
+        let index = self
+            .checkpoints
+            .iter()
+            .rposition(|checkpoint| checkpoint.operation_count <= operation_count)
+            .ok_or(err!(Internal))?;
+        self.checkpoints.truncate(index + 1);
+        let checkpoint = &self.checkpoints[index];
+        let restored: Self =
+            serde_molecule::from_slice(&checkpoint.archive, false).map_err(|_| err!(Internal))?;
+        let start = checkpoint.operation_count;
+        let mut operations = core::mem::take(&mut self.operations);
+        operations.truncate(operation_count);
+        let replaying = operations.split_off(start);
+        let checkpoints = core::mem::take(&mut self.checkpoints);
+        let undone_operations = core::mem::take(&mut self.undone_operations);
+        let setup = core::mem::take(&mut self.setup);
+        let signal_limits = self.ctx.signal_limits;
+        *self = Self {
+            operations,
+            checkpoints,
+            setup,
+            ..restored
+        };
+        self.ctx.signal_limits = signal_limits;
+        #[cfg(feature = "log")]
+        self.ctx.resume_operations(start);
+        // Recorded operations have all succeeded, so they replay without failures
+        replaying
+            .iter()
+            .try_for_each(|operation| self.apply_operation(operation))?;
+        self.undone_operations = undone_operations;
+        #[cfg(feature = "log")]
+        self.ctx.dump_logs();
+        Ok(())
+    }
+
+    // Candidates in pile order, along with the count limit of current selection
+    fn legal_selection(&self, player: Option<&PlayerRuntime>) -> LegalSelection {
+        let candidates = match (self.card_selection.source, player) {
+            (CardSelectionSource::Loot, _) => self._loot_cards.keys().copied().collect(),
+            (CardSelectionSource::Deck, Some(player)) => player.deck_cards.clone(),
+            (CardSelectionSource::Grave, Some(player)) => player.grave_cards.clone(),
+            (CardSelectionSource::Exile, Some(player)) => player.exile_cards.clone(),
+            (CardSelectionSource::Hand, Some(player)) => player.handhold_cards.clone(),
+            _ => vec![],
+        };
+        LegalSelection {
+            source: self.card_selection.source,
+            candidates,
+            max_count: self.card_selection.count,
+        }
+    }
+
+    // Affordable cards in hand along with their valid targets, cards without specific target
+    // are spelled with no target
+    fn playable_spells(&self, player: &PlayerRuntime) -> Vec<GameOperation> {
+        let enemies = self
+            .ctx
+            .runtimes
+            .collect_runtimes(RuntimeType::Enemy)
+            .into_iter()
+            .filter_map(|runtime| runtime.enemy().ok())
+            .filter(|enemy| enemy.hp > 0)
+            .map(|enemy| enemy.runtime_id)
+            .collect::<Vec<_>>();
+        player
+            .handhold_cards
+            .iter()
+            .filter_map(|runtime_id| match self.ctx.runtimes.get(runtime_id) {
+                Ok(Runtime::Card(card)) if card.cost <= player.energy => Some(card),
+                _ => None,
+            })
+            .flat_map(|card| {
+                let targets = match card.raw.target {
+                    CardTarget::Enemy => enemies.iter().copied().map(Some).collect(),
+                    CardTarget::Entity => core::iter::once(PLAYER_RUNTIME_ID)
+                        .chain(enemies.iter().copied())
+                        .map(Some)
+                        .collect(),
+                    _ => vec![None],
+                };
+                targets
+                    .into_iter()
+                    .map(|target| GameOperation::SpellCard(card.runtime_id, target))
+            })
+            .collect()
+    }
+
     fn transform_signal(&mut self, mut signal: Signal) -> Result<Option<Signal>, Error> {
         if signal.transformed {
             return Ok(Some(signal));
         }
         let mut transformed = false;
-        let mut ids = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Effect);
-        while !ids.is_empty() {
-            let effect = self.ctx.runtimes.remove(&ids.remove(0))?;
+        let ids = self.ctx.runtimes.transformer_ids().collect::<Vec<_>>();
+        for runtime_id in ids {
+            let Some(effect) = self.ctx.runtimes.detach(&runtime_id) else {
+                continue;
+            };
+            self.ctx.enter_signal(&signal, Some(effect.runtime_id()));
             let transformed_signals = effect.transform(&signal, &mut self.ctx)?;
             if !transformed_signals.is_empty() {
+                self.ctx.check_transform()?;
                 transformed = true;
+                #[cfg(feature = "log")]
+                let parent_runtime_id = effect.effect()?.parent_runtime_id;
                 push_log!(self.ctx, {
                     name: BuffApplied,
+                    event: crate::LogEvent::BuffApplied {
+                        effect: effect.runtime_id(),
+                        parent: parent_runtime_id,
+                    },
                     value: effect.runtime_id(),
-                    recipient: effect.effect().unwrap().parent_runtime_id,
+                    recipient: parent_runtime_id,
                 });
             }
+            // Replacements take the place of the signal, where the rest of effects transform
+            // them in turn
             transformed_signals.into_iter().for_each(|signal| {
-                self.ctx.signal(signal);
+                self.ctx
+                    .signal_with_priority(signal, SignalPriority::Interrupt);
             });
-            self.ctx.runtimes.insert(effect);
+            self.ctx.runtimes.attach(effect);
+            if transformed {
+                break;
+            }
         }
         if transformed {
             Ok(None)
@@ -377,50 +831,130 @@ impl PveSystemRuntime {
         }
     }
 
+    fn run_hooks(&mut self, hook: SignalHook, signal: &Signal) -> Result<Vec<Signal>, Error> {
+        let mut hooked_signals = vec![];
+        let ids = self.ctx.runtimes.hook_ids(hook).collect::<Vec<_>>();
+        for runtime_id in ids {
+            let Some(effect) = self.ctx.runtimes.detach(&runtime_id) else {
+                continue;
+            };
+            // Traps spring once the signal meeting their triggers has been run
+            if hook == SignalHook::After {
+                if let Some(mut payload) = effect.spring(signal, &mut self.ctx)? {
+                    self.consume_trap(effect)?;
+                    hooked_signals.append(&mut payload);
+                    continue;
+                }
+            }
+            hooked_signals.append(&mut effect.hook(hook, signal, &mut self.ctx)?);
+            self.ctx.runtimes.attach(effect);
+        }
+        Ok(hooked_signals)
+    }
+
+    // A sprung trap is detached from its parent and its slot is released
+    fn consume_trap(&mut self, trap: Runtime) -> Result<(), Error> {
+        let trap = trap.effect()?;
+        self.ctx.runtimes.release(&trap.runtime_id);
+        push_log!(self.ctx, {
+            name: BuffApplied,
+            event: crate::LogEvent::BuffApplied {
+                effect: trap.runtime_id,
+                parent: trap.parent_runtime_id,
+            },
+            value: trap.runtime_id,
+            recipient: trap.parent_runtime_id,
+        });
+        self.detach_effect(trap)
+    }
+
+    // Drop the effect from the active effects of its parent, while the effect runtime itself is
+    // removed or released by the caller
+    fn detach_effect(&mut self, effect: &EffectRuntime) -> Result<(), Error> {
+        // The parent may have left the battle already
+        let Ok(parent) = self.ctx.runtimes.get_mut(&effect.parent_runtime_id) else {
+            return Ok(());
+        };
+        parent
+            .active_effects_mut()
+            .map(|effects| effects.retain(|runtime_id| *runtime_id != effect.runtime_id))
+    }
+
+    fn interrupt(&mut self, signals: Vec<Signal>) {
+        signals.into_iter().for_each(|signal| {
+            self.ctx
+                .signal_with_priority(signal, SignalPriority::Interrupt);
+        });
+    }
+
     fn run_signal(&mut self, signal: Signal) -> Result<(), Error> {
         self.ctx.signal(signal);
         self.run()
     }
 
     fn run(&mut self) -> Result<(), Error> {
-        while let Some(signal) = self.ctx.pop_signal() {
+        while let Some(signal) = self.ctx.pop_signal()? {
             let Some(signal) = self.transform_signal(signal)? else {
                 continue;
             };
             if signal.name == SignalName::Skip {
                 continue;
             }
-            self.ctx.applied_signal(signal.clone());
-            let mut ids = {
-                let mut ids = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Card);
-                ids.append(&mut self.ctx.runtimes.collect_runtime_ids(RuntimeType::Enemy));
-                ids.append(&mut self.ctx.runtimes.collect_runtime_ids(RuntimeType::Effect));
-                ids.push(BATTLE_RUNTIME_ID);
-                ids.push(PLAYER_RUNTIME_ID);
-                ids.push(SYSTEM_RUNTIME_ID);
-                ids
-            };
-            while !ids.is_empty() {
-                let runtime_id = ids.remove(0);
-                if runtime_id == SYSTEM_RUNTIME_ID {
-                    self.run_self(&signal)?;
-                } else {
-                    let Ok(mut runtime) = self.ctx.runtimes.remove(&runtime_id) else {
-                        continue;
-                    };
-                    runtime.run(&signal, &mut self.ctx)?;
-                    self.ctx.runtimes.insert(runtime);
+            self.ctx.enter_signal(&signal, None);
+            if !self.ctx.hooked() {
+                let interrupts = self.run_hooks(SignalHook::Before, &signal)?;
+                if !interrupts.is_empty() {
+                    self.interrupt(interrupts);
+                    self.ctx.defer_signal(signal);
+                    continue;
                 }
             }
+            self.ctx.applied_signal(signal.clone());
+            // Subscribers are detached from their slots while running since they read other
+            // runtimes from the pool, the id buffer is reused across signals
+            let mut subscribers = core::mem::take(&mut self.subscribers);
+            subscribers.clear();
+            self.ctx
+                .runtimes
+                .collect_subscribers(&signal, &mut subscribers);
+            for runtime_id in &subscribers {
+                // Runtimes removed by former subscribers are skipped
+                let Some(mut runtime) = self.ctx.runtimes.detach(runtime_id) else {
+                    continue;
+                };
+                runtime.run(&signal, &mut self.ctx)?;
+                self.ctx.runtimes.attach(runtime);
+            }
+            self.subscribers = subscribers;
+            self.run_self(&signal)?;
+            let reactions = self.run_hooks(SignalHook::After, &signal)?;
+            self.interrupt(reactions);
         }
         self.ctx
             .dump_history_delete_runtimes()
             .into_iter()
             .try_for_each(|id| {
-                self.ctx.runtimes.remove(&id)?;
+                if let Runtime::Effect(effect) = self.ctx.runtimes.remove(&id)? {
+                    push_log!(self.ctx, {
+                        name: RemoveBuff,
+                        value: effect.runtime_id,
+                        recipient: effect.parent_runtime_id,
+                    });
+                    self.detach_effect(&effect)?;
+                }
                 Ok(())
             })?;
         if let Some(battle_level) = self.ctx.battle_level().cloned() {
+            let player = self.ctx.runtimes.get(&PLAYER_RUNTIME_ID)?.player()?;
+            if player.hp == 0 {
+                self.game_over = true;
+                push_log!(self.ctx, {
+                    name: GameOver,
+                    recipient: PLAYER_RUNTIME_ID,
+                });
+                self.ctx.clear_battle_level();
+                return self.end_battle();
+            }
             if self
                 .ctx
                 .runtimes
@@ -434,7 +968,7 @@ impl PveSystemRuntime {
                 self.ctx.clear_battle_level();
                 let target_runtime_ids = {
                     let mut ids = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Card);
-                    ids.append(&mut self.ctx.runtimes.collect_runtime_ids(RuntimeType::Effect));
+                    ids.extend(self.ctx.runtimes.effect_ids());
                     ids.push(SYSTEM_RUNTIME_ID);
                     ids.push(BATTLE_RUNTIME_ID);
                     ids.push(PLAYER_RUNTIME_ID);
@@ -447,18 +981,186 @@ impl PveSystemRuntime {
                     source_runtime_id: SYSTEM_RUNTIME_ID,
                     target_runtime_ids,
                 })?;
-                let dead_enemies = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Enemy);
-                dead_enemies.into_iter().try_for_each(|enemy_id| {
-                    self.ctx.runtimes.remove(&enemy_id)?;
-                    Ok(())
-                })?;
-                self.ctx.clear();
+                self.end_battle()?;
             }
         }
         Ok(())
     }
 
-    fn run_self(&mut self, _signal: &Signal) -> Result<(), Error> {
+    // Enemies, the battle and effects left behind, which only last in the battle, leave the
+    // pool along with the battle states of context
+    fn end_battle(&mut self) -> Result<(), Error> {
+        let effect_ids = self.ctx.runtimes.effect_ids().collect::<Vec<_>>();
+        effect_ids.iter().try_for_each(|runtime_id| {
+            if let Runtime::Effect(effect) = self.ctx.runtimes.remove(runtime_id)? {
+                self.detach_effect(&effect)?;
+            }
+            Ok(())
+        })?;
+        let mut runtime_ids = self.ctx.runtimes.collect_runtime_ids(RuntimeType::Enemy);
+        runtime_ids.push(BATTLE_RUNTIME_ID);
+        runtime_ids.iter().try_for_each(|runtime_id| {
+            self.ctx.runtimes.remove(runtime_id)?;
+            Ok(())
+        })?;
+        self.ctx.clear();
+        Ok(())
+    }
+
+    // Gold dropped by every enemy of the battle
+    fn loot_gold(&mut self) -> Result<(), Error> {
+        let gold = self
+            .ctx
+            .runtimes
+            .collect_runtimes(RuntimeType::Enemy)
+            .into_iter()
+            .map(|runtime| runtime.enemy().map(|enemy| enemy.raw.gold))
+            .sum::<Result<u16, Error>>()?;
+        self.gold = self.gold.saturating_add(gold);
+        push_log!(self.ctx, {
+            name: LootGold,
+            value: gold,
+            recipient: PLAYER_RUNTIME_ID,
+        });
+        Ok(())
+    }
+
+    fn run_self(&mut self, signal: &Signal) -> Result<(), Error> {
+        // Effects are created by the system on behalf of their parents
+        if signal.name == SignalName::AddEffect {
+            return self.add_effect(signal);
+        }
+        if !signal.is_target(SYSTEM_RUNTIME_ID) {
+            return Ok(());
+        }
+        match signal.name {
+            SignalName::InitPlayer => self.init_player(signal),
+            SignalName::InitBattle => self.init_battle(signal),
+            SignalName::BattleOver => self.loot_gold(),
+            _ => Ok(()),
+        }
+    }
+
+    fn add_effect(&mut self, signal: &Signal) -> Result<(), Error> {
+        let SignalValue::Effect(effect) = &signal.value else {
+            return Err(err!(SystemInvalidSignalType(signal.name)));
+        };
+        for parent_runtime_id in &signal.target_runtime_ids {
+            let runtime_id = self.ctx.generate_runtime_id();
+            let effect = EffectRuntime::new(*effect, *parent_runtime_id, runtime_id)?;
+            let parent = self.ctx.runtimes.get_mut(parent_runtime_id)?;
+            effect.check_parent(parent)?;
+            parent.active_effects_mut()?.push(runtime_id);
+            push_log!(self.ctx, {
+                name: AddBuff,
+                event: crate::LogEvent::BuffAdded {
+                    effect: runtime_id,
+                    parent: *parent_runtime_id,
+                    name: effect.raw.name,
+                    value: effect.value,
+                    countdown: effect.countdown,
+                },
+                value: runtime_id,
+                recipient: *parent_runtime_id,
+            });
+            self.ctx.runtimes.insert(Runtime::Effect(effect));
+        }
+        Ok(())
+    }
+
+    fn init_player(&mut self, signal: &Signal) -> Result<(), Error> {
+        let SignalValue::Player(player, action_point, gameplay_cards) = &signal.value else {
+            return Err(err!(SystemInvalidSignalType(signal.name)));
+        };
+        if self.ctx.runtimes.get(&PLAYER_RUNTIME_ID).is_ok() {
+            return Err(err!(PlayerExisted));
+        }
+        // Every gameplay card is decoded from its DNA and put into the deck
+        let card_pool = card_pool().ok_or(err!(CardCreateFailed))?;
+        let deck_cards = gameplay_cards
+            .iter()
+            .map(|dna| {
+                let card =
+                    decode_dna(card_pool, dna, self.version).ok_or(err!(CardCreateFailed))?;
+                let runtime_id = self.ctx.generate_runtime_id();
+                self.ctx.runtimes.insert(Runtime::Card(CardRuntime::new(
+                    card,
+                    self.version,
+                    PLAYER_RUNTIME_ID,
+                    runtime_id,
+                )));
+                Ok(runtime_id)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.ctx.runtimes.insert(Runtime::Player(PlayerRuntime::new(
+            player.clone(),
+            PLAYER_RUNTIME_ID,
+            deck_cards,
+        )));
+        self.action_point = *action_point;
+        push_log!(self.ctx, {
+            name: InitPlayer,
+            value: player.hp,
+            recipient: PLAYER_RUNTIME_ID,
+        });
+        push_log!(self.ctx, {
+            name: ActionPointChange,
+            value: self.action_point,
+            recipient: SYSTEM_RUNTIME_ID,
+        });
+        Ok(())
+    }
+
+    // Pay the action point of the level and spawn an enemy of the level from the enemy pool
+    // of the session version, so that old sessions keep fighting the enemies they were made with
+    fn init_battle(&mut self, signal: &Signal) -> Result<(), Error> {
+        let SignalValue::EnemyLevel(enemy_level, version) = signal.value else {
+            return Err(err!(SystemInvalidSignalType(signal.name)));
+        };
+        if self.ctx.battle_running() {
+            return Err(err!(BattleAlreadyStarted));
+        }
+        let action_point = {
+            let player = self.ctx.runtimes.get(&PLAYER_RUNTIME_ID)?.player()?;
+            match enemy_level {
+                EnemyLevel::Easy => player.raw.easy_action_point,
+                EnemyLevel::Normal => player.raw.normal_action_point,
+                EnemyLevel::Hard => player.raw.hard_action_point,
+            }
+        };
+        if self.action_point < action_point as u16 {
+            return Err(err!(SystemInsufficientActionPoint));
+        }
+        let seed = self.ctx.rng.next_u64();
+        let enemy = enemy_pool()
+            .and_then(|enemy_pool| roulette_enemy(enemy_pool, enemy_level, version, seed))
+            .ok_or(err!(EnemyLevelNotFound))?;
+        self.action_point -= action_point as u16;
+        push_log!(self.ctx, {
+            name: ActionPointChange,
+            value: self.action_point,
+            recipient: SYSTEM_RUNTIME_ID,
+        });
+        let runtime_id = self.ctx.generate_runtime_id();
+        let mut battle = PveBattleRuntime::default();
+        battle.enemies_action_flag.insert(runtime_id, false);
+        self.ctx.runtimes.insert(Runtime::PveBattle(battle));
+        push_log!(self.ctx, {
+            name: AddEnemy,
+            value: enemy.hp,
+            recipient: runtime_id,
+        });
+        let mut enemy = EnemyRuntime::new(enemy, runtime_id);
+        enemy.roll_actions(&mut self.ctx.rng);
+        self.ctx.runtimes.insert(Runtime::Enemy(enemy));
+        self.ctx.start_battle(enemy_level);
+        push_log!(self.ctx, {
+            name: EnterBattle,
+            value: enemy_level as u8,
+            recipient: BATTLE_RUNTIME_ID,
+        });
+        self.ctx
+            .signal_with_priority(PveBattleRuntime::player_turn(), SignalPriority::Low);
         Ok(())
     }
 }
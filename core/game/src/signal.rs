@@ -1,9 +1,11 @@
 use alloc::vec::Vec;
 use common::{card::Card, effect::Effect, enemy::EnemyLevel, hardcoded::DNA, player::Player};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Copy)]
-#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
 pub enum SignalName {
+    /// Replacement of a cancelled signal, which is dropped without being run
     Skip,
     InitBattle,
     BattleOver,
@@ -18,6 +20,9 @@ pub enum SignalName {
     SelectCardFromGrave,
     SelectCardFromLoot,
     SelectCardFromHand,
+    AddEffect,
+    ChangeEnergy,
+    PlayerTurn,
 }
 
 #[allow(unused)]
@@ -35,6 +40,41 @@ pub enum SignalValue {
     Empty,
 }
 
+/// Resolution order of queued signals, signals of the same priority resolve first in first out
+///
+/// A popped signal resolves in these steps:
+/// 1. effects transform it in runtime id order, the first transforming effect replaces it with
+///    the results, which the rest of effects transform in turn
+/// 2. effects run `before` hooks in runtime id order, if any signal is returned, they are queued
+///    as interrupts and the signal is queued again right after them, skipping this step later
+/// 3. subscribed runtimes run it in the order of cards, enemies, effects, battle, player and
+///    at last the system
+/// 4. effects run `after` hooks in runtime id order, or spring if they are traps meeting their
+///    triggers, the returned signals are queued as interrupts, which is how counter-attacks and
+///    "on death" triggers resolve before the rest
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum SignalPriority {
+    /// Jump the queue, only behind earlier interrupts
+    Interrupt,
+    #[default]
+    Normal,
+    /// Wait until the rest have resolved, such as the turn of player after the enemy turn
+    Low,
+}
+
+impl SignalPriority {
+    pub const COUNT: usize = 3;
+}
+
+/// Point of the signal resolution where effects hook in
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum SignalHook {
+    Before,
+    After,
+}
+
 #[derive(Clone)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct Signal {
@@ -45,6 +85,17 @@ pub struct Signal {
     pub target_runtime_ids: Vec<u16>,
 }
 
+/// One link of the causal chain of a signal, `transformed_by` is set if the next link was
+/// produced by an effect transforming this signal rather than by runtimes running it
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(any(feature = "debug", feature = "log"), derive(Debug))]
+pub struct SignalTrace {
+    pub name: SignalName,
+    pub source_runtime_id: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transformed_by: Option<u16>,
+}
+
 impl Signal {
     pub fn is_target(&self, runtime_id: u16) -> bool {
         self.target_runtime_ids.contains(&runtime_id)
@@ -0,0 +1,215 @@
+use alloc::vec::Vec;
+use common::{
+    card::Card,
+    effect::Effect,
+    enemy::{Action, Enemy, EnemyLevel},
+    player::Player,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CardRuntime, CardSelection, Context, EffectRuntime, EnemyRuntime, GameStatistics,
+    PlayerRuntime, Runtime, RuntimePool, RuntimeType, BATTLE_RUNTIME_ID, PLAYER_RUNTIME_ID,
+};
+
+/// Read-only snapshot of the whole game, which clients render from
+#[derive(Serialize, Deserialize)]
+pub struct GameView {
+    pub version: u8,
+    pub game_over: bool,
+    pub gold: u16,
+    pub action_point: u16,
+    pub statistics: GameStatistics,
+    pub player: Option<PlayerView>,
+    pub battle: Option<BattleView>,
+    pub card_selection: CardSelection,
+    pub loot_cards: Vec<LootCardView>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CardView {
+    pub runtime_id: u16,
+    pub version: u8,
+    pub card: Card,
+    pub cost: u8,
+    pub exile: bool,
+    pub awake: u8,
+}
+
+impl From<&CardRuntime> for CardView {
+    fn from(card: &CardRuntime) -> Self {
+        Self {
+            runtime_id: card.runtime_id,
+            version: card.version,
+            card: card.raw.clone(),
+            cost: card.cost,
+            exile: card.exile,
+            awake: card.awake,
+        }
+    }
+}
+
+/// Loot cards are not in game yet, so they have no runtime states except the id to select
+#[derive(Serialize, Deserialize)]
+pub struct LootCardView {
+    pub runtime_id: u16,
+    pub card: Card,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EffectView {
+    pub runtime_id: u16,
+    pub parent_runtime_id: u16,
+    pub effect: Effect,
+    pub value: u8,
+    pub countdown: u8,
+}
+
+impl From<&EffectRuntime> for EffectView {
+    fn from(effect: &EffectRuntime) -> Self {
+        Self {
+            runtime_id: effect.runtime_id,
+            parent_runtime_id: effect.parent_runtime_id,
+            effect: effect.raw,
+            value: effect.value,
+            countdown: effect.countdown,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlayerView {
+    pub runtime_id: u16,
+    pub player: Player,
+    pub energy: u8,
+    pub hp: u16,
+    pub attack: u8,
+    pub defense: u8,
+    pub spirit: u8,
+    pub block: u16,
+    pub shield: u16,
+    pub equipment_cards: Vec<CardView>,
+    pub sorcery_cards: Vec<CardView>,
+    pub handhold_cards: Vec<CardView>,
+    pub deck_cards: Vec<CardView>,
+    pub grave_cards: Vec<CardView>,
+    pub exile_cards: Vec<CardView>,
+    pub active_effects: Vec<EffectView>,
+}
+
+impl PlayerView {
+    fn new(player: &PlayerRuntime, pool: &RuntimePool) -> Self {
+        Self {
+            runtime_id: player.runtime_id,
+            player: player.raw.clone(),
+            energy: player.energy,
+            hp: player.hp,
+            attack: player.attack,
+            defense: player.defense,
+            spirit: player.spirit,
+            block: player.block,
+            shield: player.shield,
+            equipment_cards: card_views(pool, &player.equipment_cards),
+            sorcery_cards: card_views(pool, &player.sorcery_cards),
+            handhold_cards: card_views(pool, &player.handhold_cards),
+            deck_cards: card_views(pool, &player.deck_cards),
+            grave_cards: card_views(pool, &player.grave_cards),
+            exile_cards: card_views(pool, &player.exile_cards),
+            active_effects: effect_views(pool, &player.active_effects),
+        }
+    }
+}
+
+/// Enemy states, where `active_actions` are the telegraphed actions of next enemy turn
+#[derive(Serialize, Deserialize)]
+pub struct EnemyView {
+    pub runtime_id: u16,
+    pub enemy: Enemy,
+    pub hp: u16,
+    pub attack: u8,
+    pub defense: u8,
+    pub spirit: u8,
+    pub block: u16,
+    pub shield: u16,
+    pub active_actions: Vec<Action>,
+    pub active_effects: Vec<EffectView>,
+}
+
+impl EnemyView {
+    fn new(enemy: &EnemyRuntime, pool: &RuntimePool) -> Self {
+        Self {
+            runtime_id: enemy.runtime_id,
+            enemy: enemy.raw.clone(),
+            hp: enemy.hp,
+            attack: enemy.attack,
+            defense: enemy.defense,
+            spirit: enemy.spirit,
+            block: enemy.block,
+            shield: enemy.shield,
+            active_actions: enemy.active_actions.clone(),
+            active_effects: effect_views(pool, &enemy.active_effects),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BattleView {
+    pub level: EnemyLevel,
+    pub round: u8,
+    pub enemies: Vec<EnemyView>,
+    pub active_effects: Vec<EffectView>,
+}
+
+impl BattleView {
+    fn new(ctx: &Context) -> Option<Self> {
+        let level = *ctx.battle_level()?;
+        let pool = &ctx.runtimes;
+        let enemies = pool
+            .collect_runtimes(RuntimeType::Enemy)
+            .into_iter()
+            .filter_map(|runtime| runtime.enemy().ok())
+            .map(|enemy| EnemyView::new(enemy, pool))
+            .collect();
+        let active_effects = match pool.get(&BATTLE_RUNTIME_ID) {
+            Ok(Runtime::PveBattle(battle)) => effect_views(pool, &battle.active_effects),
+            _ => Vec::new(),
+        };
+        Some(Self {
+            level,
+            round: ctx.battle_round(),
+            enemies,
+            active_effects,
+        })
+    }
+}
+
+fn card_views(pool: &RuntimePool, runtime_ids: &[u16]) -> Vec<CardView> {
+    runtime_ids
+        .iter()
+        .filter_map(|runtime_id| match pool.get(runtime_id) {
+            Ok(Runtime::Card(card)) => Some(card.into()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn effect_views(pool: &RuntimePool, runtime_ids: &[u16]) -> Vec<EffectView> {
+    runtime_ids
+        .iter()
+        .filter_map(|runtime_id| match pool.get(runtime_id) {
+            Ok(Runtime::Effect(effect)) => Some(effect.into()),
+            _ => None,
+        })
+        .collect()
+}
+
+impl GameView {
+    pub(crate) fn player(ctx: &Context) -> Option<PlayerView> {
+        let player = ctx.runtimes.get(&PLAYER_RUNTIME_ID).ok()?.player().ok()?;
+        Some(PlayerView::new(player, &ctx.runtimes))
+    }
+
+    pub(crate) fn battle(ctx: &Context) -> Option<BattleView> {
+        BattleView::new(ctx)
+    }
+}
@@ -0,0 +1,225 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use common::{
+    hardcoded::{DNA, DNA_LEN},
+    operation::{GameOperation, GameOperationSet},
+};
+use core::fmt::Display;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{GameSetup, PveSystemRuntime, SignalLimits};
+
+// Game errors are not `std::error::Error` in no_std, so they are converted by message
+fn js_error(error: impl Display) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, JsError> {
+    serde_json::to_string(value).map_err(js_error)
+}
+
+// `gameplay_cards` is the concatenation of card DNAs
+fn decode_cards(gameplay_cards: &[u8]) -> Result<Vec<DNA>, JsError> {
+    if gameplay_cards.len() % DNA_LEN != 0 {
+        return Err(JsError::new("invalid gameplay cards length"));
+    }
+    Ok(gameplay_cards
+        .chunks(DNA_LEN)
+        .map(|dna| DNA::try_from(dna).unwrap())
+        .collect())
+}
+
+/// JavaScript binding of `PveSystemRuntime`, so that the web client runs the same rules as
+/// the contract does
+#[wasm_bindgen(js_name = PveSystemRuntime)]
+pub struct WasmPveSystemRuntime {
+    inner: PveSystemRuntime,
+}
+
+#[wasm_bindgen(js_class = PveSystemRuntime)]
+impl WasmPveSystemRuntime {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> Result<WasmPveSystemRuntime, JsError> {
+        let inner = PveSystemRuntime::new(seed).map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Restore the runtime from archive bytes made by `save`
+    pub fn load(archive: &[u8]) -> Result<WasmPveSystemRuntime, JsError> {
+        let inner = PveSystemRuntime::load(archive).map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Archive bytes of the runtime, which are the `archive_output` of session materials
+    pub fn save(&self) -> Result<Vec<u8>, JsError> {
+        serde_molecule::to_vec(&self.inner, false).map_err(js_error)
+    }
+
+    pub fn archivable(&self) -> bool {
+        self.inner.archivable()
+    }
+
+    /// Replay molecule bytes of `GameOperationSet` in a new game, the same as the contract does
+    pub fn replay(
+        seed: u64,
+        player_level: u8,
+        action_point: u16,
+        gameplay_cards: &[u8],
+        version: u8,
+        operations: &[u8],
+    ) -> Result<WasmPveSystemRuntime, JsError> {
+        let operation_set: GameOperationSet =
+            serde_molecule::from_slice(operations, false).map_err(js_error)?;
+        let inner = PveSystemRuntime::replay(
+            seed,
+            player_level,
+            action_point,
+            decode_cards(gameplay_cards)?,
+            version,
+            &operation_set.operations,
+        )
+        .map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Session parameters that `apply_operation` starts games and battles with
+    pub fn set_setup(
+        &mut self,
+        player_level: u8,
+        action_point: u16,
+        gameplay_cards: &[u8],
+        version: u8,
+    ) -> Result<(), JsError> {
+        self.inner.set_setup(GameSetup {
+            player_level,
+            action_point,
+            gameplay_cards: decode_cards(gameplay_cards)?,
+            version,
+        });
+        Ok(())
+    }
+
+    /// Only for debugging content, games with limits different from the contract can't be
+    /// settled on-chain
+    pub fn set_signal_limits(&mut self, max_signals_per_operation: u16, max_transform_depth: u8) {
+        self.inner.set_signal_limits(SignalLimits {
+            max_signals_per_operation,
+            max_transform_depth,
+        });
+    }
+
+    /// Apply one `GameOperation` in JSON
+    pub fn apply_operation(&mut self, operation: &str) -> Result<(), JsError> {
+        let operation: GameOperation = serde_json::from_str(operation).map_err(js_error)?;
+        self.inner.apply_operation(&operation).map_err(js_error)
+    }
+
+    pub fn start_game(
+        &mut self,
+        player_level: u8,
+        action_point: u16,
+        gameplay_cards: &[u8],
+        version: u8,
+    ) -> Result<(), JsError> {
+        let gameplay_cards = decode_cards(gameplay_cards)?;
+        self.inner
+            .start_game(player_level, action_point, gameplay_cards, version)
+            .map_err(js_error)
+    }
+
+    /// `enemy_level` follows the order of `EnemyLevel`
+    pub fn start_battle(&mut self, enemy_level: u8, version: u8) -> Result<(), JsError> {
+        self.inner
+            .start_battle(enemy_level.into(), version)
+            .map_err(js_error)
+    }
+
+    pub fn spell_card(
+        &mut self,
+        card_runtime_id: u16,
+        target_runtime_id: Option<u16>,
+    ) -> Result<(), JsError> {
+        self.inner
+            .spell_card(card_runtime_id, target_runtime_id)
+            .map_err(js_error)
+    }
+
+    pub fn select_card(&mut self, runtime_ids: Vec<u16>) -> Result<(), JsError> {
+        self.inner.select_card(runtime_ids).map_err(js_error)
+    }
+
+    pub fn round_over(&mut self) -> Result<(), JsError> {
+        self.inner.round_over().map_err(js_error)
+    }
+
+    pub fn heal_hp(&mut self) -> Result<(), JsError> {
+        self.inner.heal_hp().map_err(js_error)
+    }
+
+    pub fn destroy_card(&mut self) -> Result<(), JsError> {
+        self.inner.destroy_card().map_err(js_error)
+    }
+
+    pub fn game_over(&self) -> bool {
+        self.inner.game_over()
+    }
+
+    pub fn gold(&self) -> u16 {
+        self.inner.get_gold()
+    }
+
+    pub fn action_point(&self) -> u16 {
+        self.inner.get_action_point()
+    }
+
+    /// Snapshot of the whole game in JSON, see `GameView`
+    pub fn view(&self) -> Result<String, JsError> {
+        to_json(&self.inner.view())
+    }
+
+    /// Operations allowed in current state in JSON, see `LegalOperations`
+    pub fn legal_operations(&self) -> Result<String, JsError> {
+        to_json(&self.inner.legal_operations())
+    }
+
+    /// Statistics in JSON
+    pub fn statistics(&self) -> Result<String, JsError> {
+        to_json(self.inner.statistics())
+    }
+
+    /// Logs emitted since the last call, each in JSON which can be passed to `new Log()`
+    pub fn logs(&mut self) -> Result<Vec<String>, JsError> {
+        self.inner.logs().iter().map(to_json).collect()
+    }
+
+    /// Recorded operations in JSON
+    pub fn operations(&self) -> Result<String, JsError> {
+        to_json(self.inner.operations())
+    }
+
+    /// Recorded operations in molecule bytes, which are the `output_type` of session witness
+    pub fn operations_bin(&self) -> Vec<u8> {
+        GameOperationSet::to_vec(self.inner.operations().clone())
+    }
+
+    /// Take back the last operation, returns false if there is nothing to undo
+    pub fn undo(&mut self) -> Result<bool, JsError> {
+        self.inner.undo().map_err(js_error)
+    }
+
+    /// Apply the last undone operation again, returns false if there is nothing to redo
+    pub fn redo(&mut self) -> Result<bool, JsError> {
+        self.inner.redo().map_err(js_error)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.inner.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.inner.can_redo()
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fs, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use common::{
+    hardcoded::{CARD_VERSION, DEFAULT_GAMEPLAY_CARDS, DNA, MAX_ACTION_POINT},
+    operation::GameOperation,
+};
+use game_core::{GameSetup, PveSystemRuntime};
+
+mod native;
+mod report;
+mod vm;
+
+/// Measure the cost of replaying recorded game operations
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replay operations with native game-core and measure elapsed time per operation type
+    Native(SessionArgs),
+    /// Verify `PveUpdate`/`PveSettlement` of game contract in CKB-VM and measure cycles
+    Vm {
+        #[command(flatten)]
+        session: SessionArgs,
+        /// Path of the compiled game contract
+        #[arg(long, default_value = "build/release/game")]
+        binary: PathBuf,
+    },
+}
+
+#[derive(Args)]
+pub struct SessionArgs {
+    /// JSON file of the recorded `GameOperation` array
+    #[arg(long)]
+    pub operations: PathBuf,
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    #[arg(long, default_value_t = 1)]
+    pub player_level: u8,
+    #[arg(long, default_value_t = MAX_ACTION_POINT)]
+    pub action_point: u16,
+    #[arg(long, default_value_t = CARD_VERSION)]
+    pub version: u8,
+}
+
+impl SessionArgs {
+    pub fn load_operations(&self) -> Result<Vec<GameOperation>, String> {
+        let content = fs::read(&self.operations).map_err(|error| error.to_string())?;
+        serde_json::from_slice(&content).map_err(|error| error.to_string())
+    }
+
+    pub fn gameplay_cards(&self) -> Vec<DNA> {
+        DEFAULT_GAMEPLAY_CARDS.to_vec()
+    }
+
+    /// New game of this session, ready to apply operations
+    pub fn new_game(&self) -> Result<PveSystemRuntime, String> {
+        let mut game = PveSystemRuntime::new(self.seed).map_err(|error| error.to_string())?;
+        game.set_setup(GameSetup {
+            player_level: self.player_level,
+            action_point: self.action_point,
+            gameplay_cards: self.gameplay_cards(),
+            version: self.version,
+        });
+        Ok(game)
+    }
+}
+
+fn main() {
+    let result = match Cli::parse().command {
+        Command::Native(session) => native::run(&session).map(|report| report.print("ns")),
+        Command::Vm { session, binary } => vm::run(&session, &binary),
+    };
+    if let Err(error) = result {
+        eprintln!("profile failed: {error}");
+        std::process::exit(1);
+    }
+}
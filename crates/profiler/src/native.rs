@@ -0,0 +1,17 @@
+use std::time::Instant;
+
+use crate::{report::Report, SessionArgs};
+
+/// Replay the whole operation set natively, timing each operation separately
+pub fn run(session: &SessionArgs) -> Result<Report, String> {
+    let operations = session.load_operations()?;
+    let mut game = session.new_game()?;
+    let mut report = Report::default();
+    for (i, operation) in operations.iter().enumerate() {
+        let start = Instant::now();
+        game.apply_operation(operation)
+            .map_err(|error| format!("operation #{i} failed: {error}"))?;
+        report.record(operation, start.elapsed().as_nanos() as u64);
+    }
+    Ok(report)
+}
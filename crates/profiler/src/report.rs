@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use common::operation::GameOperation;
+
+pub fn operation_name(operation: &GameOperation) -> &'static str {
+    match operation {
+        GameOperation::StartGame => "StartGame",
+        GameOperation::StartBattle(_) => "StartBattle",
+        GameOperation::RoundOver => "RoundOver",
+        GameOperation::SpellCard(_, _) => "SpellCard",
+        GameOperation::SelectCard(_) => "SelectCard",
+        GameOperation::HealHp => "HealHp",
+        GameOperation::DestroyCard => "DestroyCard",
+    }
+}
+
+#[derive(Default)]
+struct Entry {
+    count: u64,
+    total: u64,
+    max: u64,
+}
+
+/// Cost statistics grouped by operation type
+#[derive(Default)]
+pub struct Report {
+    entries: BTreeMap<&'static str, Entry>,
+}
+
+impl Report {
+    pub fn record(&mut self, operation: &GameOperation, cost: u64) {
+        let entry = self.entries.entry(operation_name(operation)).or_default();
+        entry.count += 1;
+        entry.total += cost;
+        entry.max = entry.max.max(cost);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.entries.values().map(|entry| entry.total).sum()
+    }
+
+    pub fn print(&self, unit: &str) {
+        println!(
+            "{:<12} {:>8} {:>16} {:>16} {:>16}",
+            "operation", "count", "total", "average", "max"
+        );
+        self.entries.iter().for_each(|(name, entry)| {
+            println!(
+                "{:<12} {:>8} {:>16} {:>16} {:>16}",
+                name,
+                entry.count,
+                entry.total,
+                entry.total / entry.count,
+                entry.max
+            );
+        });
+        println!("total: {} {unit}", self.total());
+    }
+}
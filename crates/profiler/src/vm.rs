@@ -0,0 +1,176 @@
+use std::{fs, path::Path};
+
+use crate::{report::Report, SessionArgs};
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_hash::blake2b_256,
+    ckb_types::{
+        bytes::Bytes,
+        core::{HeaderBuilder, TransactionBuilder, TransactionView},
+        packed::{CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+        prelude::*,
+    },
+    context::Context,
+};
+use common::{
+    cell::{GameGlobal, PveSession, PveSessionMaterials},
+    operation::{GameOperation, GameOperationSet},
+};
+
+const MAX_CYCLES: u64 = 3_500_000_000;
+const GAME_DATA_SCRIPT_TYPE: u8 = 0;
+const PVE_SESSION_SCRIPT_TYPE: u8 = 2;
+const CELL_CAPACITY: u64 = 1000 * 100_000_000;
+
+/// Mocked chain environment of the game contract, in which pve session transactions are built
+struct SessionChain {
+    context: Context,
+    game_out_point: OutPoint,
+    always_success: Script,
+}
+
+impl SessionChain {
+    fn new(binary: Bytes) -> Self {
+        let mut context = Context::default();
+        let game_out_point = context.deploy_cell(binary);
+        let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+        let always_success = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("always success script");
+        Self {
+            context,
+            game_out_point,
+            always_success,
+        }
+    }
+
+    fn game_script(&mut self, script_type: u8, payload: &[u8]) -> Script {
+        let args = [&[script_type], payload].concat();
+        self.context
+            .build_script(&self.game_out_point, Bytes::from(args))
+            .expect("game script")
+    }
+
+    fn build_session_tx(
+        &mut self,
+        session: &SessionArgs,
+        operations: &[GameOperation],
+        archive_output: Vec<u8>,
+        settlement: bool,
+    ) -> TransactionView {
+        let materials = PveSessionMaterials {
+            dna_collection: session.gameplay_cards(),
+            archive_input: vec![],
+            archive_output,
+        };
+        let materials_bin = serde_molecule::to_vec(&materials, false).expect("materials");
+        let session_data = PveSession {
+            version: session.version,
+            action_point: session.action_point,
+            player_level: session.player_level,
+            material_hash: blake2b_256(&materials_bin),
+        };
+        let session_bin = Bytes::from(serde_molecule::to_vec(&session_data, false).unwrap());
+
+        // Game seed comes from the block header of pve session cell
+        let global_type = self.game_script(GAME_DATA_SCRIPT_TYPE, &[]);
+        let global_type_hash = global_type.calc_script_hash();
+        let session_lock = self.game_script(PVE_SESSION_SCRIPT_TYPE, global_type_hash.as_slice());
+        let session_output = CellOutput::new_builder()
+            .capacity(CELL_CAPACITY.pack())
+            .lock(session_lock)
+            .type_(Some(self.always_success.clone()).pack())
+            .build();
+        let session_out_point = self
+            .context
+            .create_cell(session_output.clone(), session_bin.clone());
+        let header = HeaderBuilder::default()
+            .nonce((session.seed as u128).pack())
+            .build();
+        self.context.insert_header(header.clone());
+        self.context
+            .link_cell_with_block(session_out_point.clone(), header.hash(), 0);
+
+        let witness = WitnessArgs::new_builder()
+            .input_type(Some(Bytes::from(materials_bin)).pack())
+            .output_type(Some(Bytes::from(GameOperationSet::to_vec(operations.to_vec()))).pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .input(
+                CellInput::new_builder()
+                    .previous_output(session_out_point)
+                    .build(),
+            )
+            .header_dep(header.hash())
+            .witness(witness.as_bytes().pack());
+
+        // Settlement burns the pve session cell and iterates the game global cell
+        let tx = if settlement {
+            let global_output = CellOutput::new_builder()
+                .capacity(CELL_CAPACITY.pack())
+                .lock(self.always_success.clone())
+                .type_(Some(global_type).pack())
+                .build();
+            let global_bin =
+                Bytes::from(serde_molecule::to_vec(&GameGlobal::default(), false).unwrap());
+            let global_out_point = self
+                .context
+                .create_cell(global_output.clone(), global_bin.clone());
+            tx.input(
+                CellInput::new_builder()
+                    .previous_output(global_out_point)
+                    .build(),
+            )
+            .output(global_output)
+            .output_data(global_bin.pack())
+        } else {
+            tx.output(session_output).output_data(session_bin.pack())
+        };
+        self.context.complete_tx(tx.build())
+    }
+
+    fn verify(&self, tx: &TransactionView) -> Result<u64, String> {
+        self.context
+            .verify_tx(tx, MAX_CYCLES)
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// Verify the session with growing operation prefixes, the cycles increased by each prefix
+/// are attributed to the type of its last operation
+pub fn run(session: &SessionArgs, binary: &Path) -> Result<(), String> {
+    let binary = fs::read(binary).map_err(|error| error.to_string())?;
+    let operations = session.load_operations()?;
+    let mut chain = SessionChain::new(Bytes::from(binary));
+    let mut game = session.new_game()?;
+    let mut report = Report::default();
+
+    let archive = serde_molecule::to_vec(&game, false).map_err(|error| error.to_string())?;
+    let tx = chain.build_session_tx(session, &[], archive, false);
+    let baseline = chain.verify(&tx)?;
+    let mut last_cycles = baseline;
+    for (i, operation) in operations.iter().enumerate() {
+        game.apply_operation(operation)
+            .map_err(|error| error.to_string())?;
+        if game.game_over() {
+            println!("game over at operation #{i}, the rest is left to settlement");
+            break;
+        }
+        let archive = serde_molecule::to_vec(&game, false).map_err(|error| error.to_string())?;
+        let tx = chain.build_session_tx(session, &operations[..=i], archive, false);
+        let cycles = chain
+            .verify(&tx)
+            .map_err(|error| format!("PveUpdate failed at operation #{i}: {error}"))?;
+        report.record(operation, cycles.saturating_sub(last_cycles));
+        last_cycles = cycles;
+    }
+
+    println!("PveUpdate baseline: {baseline} cycles");
+    report.print("cycles");
+    let tx = chain.build_session_tx(session, &operations, vec![], true);
+    let cycles = chain
+        .verify(&tx)
+        .map_err(|error| format!("PveSettlement failed: {error}"))?;
+    println!("PveSettlement: {cycles} cycles");
+    Ok(())
+}
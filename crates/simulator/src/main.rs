@@ -0,0 +1,175 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use common::{
+    card::CardPool,
+    enemy::{EnemyLevel, EnemyPool},
+    hardcoded::{CARD_VERSION, DEFAULT_GAMEPLAY_CARDS, MAX_ACTION_POINT},
+    operation::GameOperation,
+    player::PlayerPool,
+};
+use game_core::{Error, GameSetup, PveSystemRuntime};
+
+mod report;
+mod strategy;
+
+use report::Report;
+use strategy::{Strategy, StrategyKind};
+
+/// Play seeded game sessions with automated strategies and report balance statistics
+#[derive(Parser)]
+struct Cli {
+    /// Sessions to play for each strategy
+    #[arg(long, default_value_t = 1000)]
+    sessions: u64,
+    /// Seed of the first session, the following sessions increase it by one
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    #[arg(long, default_value_t = 1)]
+    player_level: u8,
+    #[arg(long, default_value_t = MAX_ACTION_POINT)]
+    action_point: u16,
+    #[arg(long, default_value_t = CARD_VERSION)]
+    version: u8,
+    /// Strategies to play with, all strategies are played if not provided
+    #[arg(long = "strategy", value_enum)]
+    strategies: Vec<StrategyKind>,
+    /// Stop a session after this many operations, in case a strategy never ends the game
+    #[arg(long, default_value_t = 2000)]
+    max_operations: u64,
+    /// Molecule bin of the card pool, the database bin is played if no pool is provided
+    #[arg(long, requires_all = ["enemy_pool", "player_pool"])]
+    card_pool: Option<PathBuf>,
+    /// Molecule bin of the enemy pool, which must be provided along with the card pool
+    #[arg(long, requires_all = ["card_pool", "player_pool"])]
+    enemy_pool: Option<PathBuf>,
+    /// Molecule bin of the player pool, which must be provided along with the card pool
+    #[arg(long, requires_all = ["card_pool", "enemy_pool"])]
+    player_pool: Option<PathBuf>,
+}
+
+fn load_pool<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T, String> {
+    let content = fs::read(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    serde_molecule::from_slice(&content, false)
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+// Install the pools of command line before any lookup, then make sure every pool is playable,
+// otherwise each session would be rejected at its first operation
+fn install_pools(cli: &Cli) -> Result<(), String> {
+    if let (Some(card_pool), Some(enemy_pool), Some(player_pool)) =
+        (&cli.card_pool, &cli.enemy_pool, &cli.player_pool)
+    {
+        let card_pool: CardPool = load_pool(card_pool)?;
+        let enemy_pool: EnemyPool = load_pool(enemy_pool)?;
+        let player_pool: PlayerPool = load_pool(player_pool)?;
+        if !game_core::install_pools(card_pool, enemy_pool, player_pool) {
+            return Err("pools have been looked up before installed".to_string());
+        }
+    }
+    if game_core::card_pool().is_none() {
+        return Err("no card pool available, provide --card-pool or fill the database".into());
+    }
+    if game_core::enemy_pool().is_none() {
+        return Err("no enemy pool available, provide --enemy-pool or fill the database".into());
+    }
+    if game_core::player_pool().is_none() {
+        return Err("no player pool available, provide --player-pool or fill the database".into());
+    }
+    Ok(())
+}
+
+// Play until the game is over, no operation is legal, or the operation limit is reached
+fn play_session(
+    strategy: &mut dyn Strategy,
+    seed: u64,
+    cli: &Cli,
+    report: &mut Report,
+) -> Result<(), Error> {
+    let mut game = PveSystemRuntime::new(seed)?;
+    game.set_setup(GameSetup {
+        player_level: cli.player_level,
+        action_point: cli.action_point,
+        gameplay_cards: DEFAULT_GAMEPLAY_CARDS.to_vec(),
+        version: cli.version,
+    });
+    let mut battle: Option<(EnemyLevel, u8)> = None;
+    let mut operations = 0;
+    while !game.game_over() && operations < cli.max_operations {
+        let legal = game.legal_operations();
+        if legal.is_empty() {
+            break;
+        }
+        let view = game.view();
+        let operation = strategy.choose(&view, &legal);
+        if let Err(error) = game.apply_operation(&operation) {
+            report.record_rejection(error.to_string());
+            break;
+        }
+        operations += 1;
+        match &operation {
+            GameOperation::StartBattle(level) => battle = Some((*level, 1)),
+            GameOperation::RoundOver => {
+                if let Some((_, rounds)) = battle.as_mut() {
+                    *rounds += 1;
+                }
+            }
+            GameOperation::SpellCard(card_runtime_id, _) => {
+                let card = view.player.as_ref().and_then(|player| {
+                    player
+                        .handhold_cards
+                        .iter()
+                        .find(|card| card.runtime_id == *card_runtime_id)
+                });
+                if let Some(card) = card {
+                    report.record_card(card.card.name.to_string());
+                }
+            }
+            _ => {}
+        }
+        let battle_over = game.game_over() || game.view().battle.is_none();
+        if let (true, Some((level, rounds))) = (battle_over, battle) {
+            report.record_battle(level, rounds, Some(!game.game_over()));
+            battle = None;
+        }
+    }
+    if let Some((level, rounds)) = battle {
+        report.record_battle(level, rounds, None);
+    }
+    report.record_session(
+        operations,
+        game.game_over(),
+        game.get_gold(),
+        game.statistics().clone(),
+    );
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(error) = install_pools(&cli) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+    let mut strategies = cli.strategies.clone();
+    if strategies.is_empty() {
+        strategies = vec![
+            StrategyKind::Random,
+            StrategyKind::Greedy,
+            StrategyKind::Heuristic,
+        ];
+    }
+    strategies.sort();
+    strategies.dedup();
+    for kind in strategies {
+        let mut report = Report::default();
+        for seed in cli.seed..cli.seed + cli.sessions {
+            let mut strategy = kind.build(seed);
+            if let Err(error) = play_session(strategy.as_mut(), seed, &cli, &mut report) {
+                eprintln!("session {seed} failed: {error}");
+                std::process::exit(1);
+            }
+        }
+        report.print(kind.name());
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use common::enemy::EnemyLevel;
+use game_core::GameStatistics;
+
+const LEVELS: [(EnemyLevel, &str); 3] = [
+    (EnemyLevel::Easy, "Easy"),
+    (EnemyLevel::Normal, "Normal"),
+    (EnemyLevel::Hard, "Hard"),
+];
+
+fn level_index(level: EnemyLevel) -> usize {
+    match level {
+        EnemyLevel::Easy => 0,
+        EnemyLevel::Normal => 1,
+        EnemyLevel::Hard => 2,
+    }
+}
+
+#[derive(Default)]
+struct BattleEntry {
+    won: u64,
+    lost: u64,
+    unfinished: u64,
+    rounds: u64,
+}
+
+/// Balance statistics of all simulated sessions of one strategy
+#[derive(Default)]
+pub struct Report {
+    sessions: u64,
+    game_over: u64,
+    operations: u64,
+    gold: u64,
+    battles: [BattleEntry; 3],
+    card_usage: BTreeMap<String, u64>,
+    statistics: Vec<GameStatistics>,
+    rejected: BTreeMap<String, u64>,
+}
+
+impl Report {
+    pub fn record_battle(&mut self, level: EnemyLevel, rounds: u8, won: Option<bool>) {
+        let entry = &mut self.battles[level_index(level)];
+        match won {
+            Some(true) => entry.won += 1,
+            Some(false) => entry.lost += 1,
+            None => entry.unfinished += 1,
+        }
+        entry.rounds += rounds as u64;
+    }
+
+    pub fn record_card(&mut self, card: String) {
+        *self.card_usage.entry(card).or_default() += 1;
+    }
+
+    /// A legal operation rejected by the runtime, which is either a bug of `legal_operations`
+    /// or of the content
+    pub fn record_rejection(&mut self, error: String) {
+        *self.rejected.entry(error).or_default() += 1;
+    }
+
+    pub fn record_session(
+        &mut self,
+        operations: u64,
+        game_over: bool,
+        gold: u16,
+        statistics: GameStatistics,
+    ) {
+        self.sessions += 1;
+        self.operations += operations;
+        self.game_over += game_over as u64;
+        self.gold += gold as u64;
+        self.statistics.push(statistics);
+    }
+
+    fn average(&self, value: impl Fn(&GameStatistics) -> u16) -> f64 {
+        let total: u64 = self.statistics.iter().map(|s| value(s) as u64).sum();
+        total as f64 / self.sessions.max(1) as f64
+    }
+
+    pub fn print(&self, strategy: &str) {
+        let sessions = self.sessions.max(1) as f64;
+        println!("== strategy: {strategy} ==");
+        println!(
+            "sessions: {}, game over: {}, average operations: {:.1}, average gold: {:.1}",
+            self.sessions,
+            self.game_over,
+            self.operations as f64 / sessions,
+            self.gold as f64 / sessions
+        );
+        println!(
+            "{:<8} {:>8} {:>8} {:>8} {:>10} {:>10} {:>14}",
+            "level", "battles", "won", "lost", "unfinished", "win rate", "rounds/battle"
+        );
+        LEVELS.iter().for_each(|(level, name)| {
+            let entry = &self.battles[level_index(*level)];
+            let battles = entry.won + entry.lost + entry.unfinished;
+            let finished = (entry.won + entry.lost).max(1) as f64;
+            println!(
+                "{:<8} {:>8} {:>8} {:>8} {:>10} {:>9.1}% {:>14.1}",
+                name,
+                battles,
+                entry.won,
+                entry.lost,
+                entry.unfinished,
+                entry.won as f64 * 100.0 / finished,
+                entry.rounds as f64 / battles.max(1) as f64
+            );
+        });
+        println!(
+            "average per session: killed {:.1}, caused damage {:.1}, suffered damage {:.1}, blocked damage {:.1}, healed hp {:.1}",
+            self.average(|s| s.killed_enemy_count),
+            self.average(|s| s.casued_damage),
+            self.average(|s| s.sufferred_damage),
+            self.average(|s| s.blocked_damage),
+            self.average(|s| s.healed_hp),
+        );
+        println!("card usage:");
+        let mut card_usage = self.card_usage.iter().collect::<Vec<_>>();
+        card_usage.sort_by(|(_, left), (_, right)| right.cmp(left));
+        card_usage.iter().for_each(|(card, count)| {
+            println!("  {card:<24} {count:>8}");
+        });
+        if !self.rejected.is_empty() {
+            println!("rejected legal operations:");
+            self.rejected.iter().for_each(|(error, count)| {
+                println!("  {error:<24} {count:>8}");
+            });
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use clap::ValueEnum;
+use common::{card::CardCategory, enemy::EnemyLevel, operation::GameOperation};
+use game_core::{CardView, GameView, LegalOperations, LegalSelection, RandGenerator};
+
+/// Decision maker of a simulated player, which picks one of the legal operations at each step
+pub trait Strategy {
+    /// `legal` is never empty
+    fn choose(&mut self, view: &GameView, legal: &LegalOperations) -> GameOperation;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum StrategyKind {
+    /// Pick any legal operation uniformly
+    Random,
+    /// Always deal as much damage as possible and fight the hardest affordable battle
+    Greedy,
+    /// Protect hp first, heal between battles and avoid hard battles
+    Heuristic,
+}
+
+impl StrategyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Greedy => "greedy",
+            Self::Heuristic => "heuristic",
+        }
+    }
+
+    pub fn build(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            Self::Random => Box::new(RandomStrategy {
+                rng: RandGenerator::new(seed),
+            }),
+            Self::Greedy => Box::new(GreedyStrategy),
+            Self::Heuristic => Box::new(HeuristicStrategy),
+        }
+    }
+}
+
+pub struct RandomStrategy {
+    rng: RandGenerator,
+}
+
+impl Strategy for RandomStrategy {
+    // A pending selection counts as one more choice, which then picks random candidates
+    fn choose(&mut self, _view: &GameView, legal: &LegalOperations) -> GameOperation {
+        let choices = legal.operations.len() + legal.selection.is_some() as usize;
+        let index = self.rng.next_u64() as usize % choices;
+        match (legal.operations.get(index), &legal.selection) {
+            (Some(operation), _) => operation.clone(),
+            (None, Some(selection)) => self.random_selection(selection),
+            (None, None) => unreachable!("legal operations are never empty"),
+        }
+    }
+}
+
+impl RandomStrategy {
+    fn random_selection(&mut self, selection: &LegalSelection) -> GameOperation {
+        let mut candidates = selection.candidates.clone();
+        let limit = selection.max_count.min(candidates.len());
+        let count = self.rng.next_u64() as usize % (limit + 1);
+        let runtime_ids = (0..count)
+            .map(|_| {
+                let index = self.rng.next_u64() as usize % candidates.len();
+                candidates.remove(index)
+            })
+            .collect();
+        GameOperation::SelectCard(runtime_ids)
+    }
+}
+
+pub struct GreedyStrategy;
+
+impl Strategy for GreedyStrategy {
+    fn choose(&mut self, view: &GameView, legal: &LegalOperations) -> GameOperation {
+        let legal_operations = legal.operations.as_slice();
+        largest_selection(legal)
+            .or_else(|| {
+                best_spell(view, legal_operations, |card| {
+                    (card.card.category == CardCategory::Attack, card.cost)
+                })
+            })
+            .or_else(|| {
+                find(legal_operations, |operation| {
+                    matches!(operation, GameOperation::RoundOver)
+                })
+            })
+            .or_else(|| {
+                battle_by_level(
+                    legal_operations,
+                    &[EnemyLevel::Hard, EnemyLevel::Normal, EnemyLevel::Easy],
+                )
+            })
+            .unwrap_or_else(|| legal_operations[0].clone())
+    }
+}
+
+pub struct HeuristicStrategy;
+
+impl Strategy for HeuristicStrategy {
+    fn choose(&mut self, view: &GameView, legal: &LegalOperations) -> GameOperation {
+        let legal_operations = legal.operations.as_slice();
+        let hp_percent = view
+            .player
+            .as_ref()
+            .map(|player| player.hp as u32 * 100 / player.player.hp.max(1) as u32)
+            .unwrap_or(100);
+        let defensive = hp_percent < 50;
+        largest_selection(legal)
+            .or_else(|| {
+                best_spell(view, legal_operations, |card| {
+                    let protective = matches!(
+                        card.card.category,
+                        CardCategory::Defense | CardCategory::Recover
+                    );
+                    // Cheaper cards first so that more cards are played in one round
+                    (protective == defensive, u8::MAX - card.cost)
+                })
+            })
+            .or_else(|| {
+                find(legal_operations, |operation| {
+                    matches!(operation, GameOperation::RoundOver)
+                })
+            })
+            .or_else(|| {
+                (hp_percent < 60)
+                    .then(|| {
+                        find(legal_operations, |operation| {
+                            matches!(operation, GameOperation::HealHp)
+                        })
+                    })
+                    .flatten()
+            })
+            .or_else(|| {
+                let levels = if hp_percent < 80 {
+                    [EnemyLevel::Easy].as_slice()
+                } else {
+                    [EnemyLevel::Normal, EnemyLevel::Easy].as_slice()
+                };
+                battle_by_level(legal_operations, levels)
+            })
+            .unwrap_or_else(|| legal_operations[0].clone())
+    }
+}
+
+fn find(
+    legal_operations: &[GameOperation],
+    predicate: impl Fn(&GameOperation) -> bool,
+) -> Option<GameOperation> {
+    legal_operations
+        .iter()
+        .find(|operation| predicate(operation))
+        .cloned()
+}
+
+// Take as many cards as the selection allows, loot cards are always worth taking
+fn largest_selection(legal: &LegalOperations) -> Option<GameOperation> {
+    legal.selection.as_ref().map(|selection| {
+        let runtime_ids = selection
+            .candidates
+            .iter()
+            .take(selection.max_count)
+            .copied()
+            .collect();
+        GameOperation::SelectCard(runtime_ids)
+    })
+}
+
+fn battle_by_level(
+    legal_operations: &[GameOperation],
+    levels: &[EnemyLevel],
+) -> Option<GameOperation> {
+    levels.iter().find_map(|level| {
+        find(legal_operations, |operation| {
+            matches!(operation, GameOperation::StartBattle(enemy_level) if enemy_level == level)
+        })
+    })
+}
+
+// Spell the card with the highest score onto the enemy with the lowest hp
+fn best_spell<S: Ord>(
+    view: &GameView,
+    legal_operations: &[GameOperation],
+    score: impl Fn(&CardView) -> S,
+) -> Option<GameOperation> {
+    let player = view.player.as_ref()?;
+    let enemy_hp = |runtime_id: u16| {
+        view.battle
+            .as_ref()
+            .and_then(|battle| {
+                battle
+                    .enemies
+                    .iter()
+                    .find(|enemy| enemy.runtime_id == runtime_id)
+            })
+            .map(|enemy| enemy.hp)
+            .unwrap_or(u16::MAX)
+    };
+    legal_operations
+        .iter()
+        .filter_map(|operation| match operation {
+            GameOperation::SpellCard(card_runtime_id, target) => player
+                .handhold_cards
+                .iter()
+                .find(|card| card.runtime_id == *card_runtime_id)
+                .map(|card| (operation, score(card), *target)),
+            _ => None,
+        })
+        .max_by(
+            |(_, left_score, left_target), (_, right_score, right_target)| {
+                let left_hp = left_target.map(enemy_hp).unwrap_or(u16::MAX);
+                let right_hp = right_target.map(enemy_hp).unwrap_or(u16::MAX);
+                left_score.cmp(right_score).then(right_hp.cmp(&left_hp))
+            },
+        )
+        .map(|(operation, _, _)| operation.clone())
+}
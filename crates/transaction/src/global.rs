@@ -0,0 +1,28 @@
+use ckb_cinnabar_calculator::{instruction::Instruction, re_exports::ckb_types::H256, rpc::RPC};
+use common::{cell::GameGlobal, hardcoded::MAX_ACTION_POINT};
+use eyre::eyre;
+
+use crate::{to_molecule, GameDeployment, IterateGameCell};
+
+/// Charge `amount` action points into the game global cell, which can't exceed the cap
+pub fn charge_action_point<T: RPC>(
+    deployment: &GameDeployment,
+    global_out_point: (H256, u32),
+    mut global: GameGlobal,
+    amount: u16,
+) -> eyre::Result<Instruction<T>> {
+    global.action_point = global
+        .action_point
+        .checked_add(amount)
+        .filter(|action_point| *action_point <= MAX_ACTION_POINT)
+        .ok_or_else(|| eyre!("action point overflow"))?;
+    let (tx_hash, index) = global_out_point;
+    Ok(Instruction::new(vec![
+        deployment.cell_dep(),
+        Box::new(IterateGameCell {
+            tx_hash,
+            index,
+            data: to_molecule(&global)?,
+        }),
+    ]))
+}
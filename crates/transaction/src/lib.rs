@@ -0,0 +1,65 @@
+use ckb_cinnabar_calculator::{
+    operation::{basic::AddCellDep, Operation},
+    re_exports::ckb_types::{core::DepType, packed::Script, prelude::*, H256},
+    rpc::RPC,
+};
+
+mod global;
+mod operation;
+mod session;
+mod spore;
+
+pub use global::*;
+pub use operation::*;
+pub use session::*;
+pub use spore::*;
+
+// Script type flags, which are the first byte of game script args
+const GAME_DATA_SCRIPT_TYPE: u8 = 0;
+const PVE_SESSION_SCRIPT_TYPE: u8 = 2;
+const SPORE_LOCKUP_SCRIPT_TYPE: u8 = 5;
+
+/// Where the game contract is deployed, and the script referencing it
+pub struct GameDeployment {
+    pub tx_hash: H256,
+    pub index: u32,
+    pub script: Script,
+}
+
+impl GameDeployment {
+    fn script(&self, script_type: u8, payload: &[u8]) -> Script {
+        let args = [&[script_type], payload].concat();
+        self.script.clone().as_builder().args(args.pack()).build()
+    }
+
+    /// Type script of the game global cell
+    pub fn game_global_type(&self) -> Script {
+        self.script(GAME_DATA_SCRIPT_TYPE, &[])
+    }
+
+    /// Lock script of the pve session cell, which pairs with the game global cell
+    pub fn pve_session_lock(&self) -> Script {
+        let global_type_hash = self.game_global_type().calc_script_hash();
+        self.script(PVE_SESSION_SCRIPT_TYPE, global_type_hash.as_slice())
+    }
+
+    /// Lock script of the spores locked up by pve sessions, which pairs with the game global cell
+    pub fn spore_lockup_lock(&self) -> Script {
+        let global_type_hash = self.game_global_type().calc_script_hash();
+        self.script(SPORE_LOCKUP_SCRIPT_TYPE, global_type_hash.as_slice())
+    }
+
+    fn cell_dep<T: RPC>(&self) -> Box<dyn Operation<T>> {
+        Box::new(AddCellDep {
+            name: "game".into(),
+            tx_hash: self.tx_hash.clone(),
+            index: self.index,
+            dep_type: DepType::Code,
+            with_data: false,
+        })
+    }
+}
+
+fn to_molecule<V: serde::Serialize>(value: &V) -> eyre::Result<Vec<u8>> {
+    serde_molecule::to_vec(value, false).map_err(|error| eyre::eyre!("molecule: {error}"))
+}
@@ -0,0 +1,118 @@
+use async_trait::async_trait;
+use ckb_cinnabar_calculator::{
+    operation::{Log, Operation},
+    re_exports::ckb_types::{packed::Script, prelude::*, H256},
+    rpc::RPC,
+    skeleton::{CellInputEx, CellOutputEx, TransactionSkeleton, WitnessArgsEx},
+};
+
+/// Push the witness of game session, whose `input_type` carries the session materials and
+/// `output_type` carries the operations
+///
+/// The game contract reads the first witness of the session group, so this operation must
+/// follow right after the session cell is pushed as the first input
+pub struct AddGameWitness {
+    pub materials: Vec<u8>,
+    pub operations: Vec<u8>,
+}
+
+#[async_trait]
+impl<T: RPC> Operation<T> for AddGameWitness {
+    async fn run(
+        self: Box<Self>,
+        _rpc: &T,
+        skeleton: &mut TransactionSkeleton,
+        _log: &mut Log,
+    ) -> eyre::Result<()> {
+        skeleton.witness(WitnessArgsEx::new(vec![], self.materials, self.operations));
+        Ok(())
+    }
+}
+
+/// Consume a game cell and recreate it with the same lock and type but new data, which is
+/// how game global cell and pve session cell iterate
+pub struct IterateGameCell {
+    pub tx_hash: H256,
+    pub index: u32,
+    pub data: Vec<u8>,
+}
+
+#[async_trait]
+impl<T: RPC> Operation<T> for IterateGameCell {
+    async fn run(
+        self: Box<Self>,
+        rpc: &T,
+        skeleton: &mut TransactionSkeleton,
+        _log: &mut Log,
+    ) -> eyre::Result<()> {
+        let input =
+            CellInputEx::new_from_outpoint(rpc, self.tx_hash, self.index, None, false).await?;
+        let output = CellOutputEx::new(input.output.output.clone(), self.data);
+        skeleton.input(input)?.output(output);
+        Ok(())
+    }
+}
+
+// Consume spore cells and recreate them under `lock`, their type and data are kept untouched
+async fn move_spores<T: RPC>(
+    rpc: &T,
+    skeleton: &mut TransactionSkeleton,
+    out_points: Vec<(H256, u32)>,
+    lock: Script,
+    since: Option<u64>,
+) -> eyre::Result<()> {
+    for (tx_hash, index) in out_points {
+        let input = CellInputEx::new_from_outpoint(rpc, tx_hash, index, since, true).await?;
+        let spore = input
+            .output
+            .output
+            .clone()
+            .as_builder()
+            .lock(lock.clone())
+            .build();
+        let output = CellOutputEx::new(spore, input.output.data.clone());
+        skeleton.input(input)?.output(output);
+    }
+    Ok(())
+}
+
+/// Lock spore cells of the player up by the game contract, which are the gameplay cards of a
+/// pve session until it is settled
+pub struct LockupSporeCells {
+    pub out_points: Vec<(H256, u32)>,
+    pub lock: Script,
+}
+
+#[async_trait]
+impl<T: RPC> Operation<T> for LockupSporeCells {
+    async fn run(
+        self: Box<Self>,
+        rpc: &T,
+        skeleton: &mut TransactionSkeleton,
+        _log: &mut Log,
+    ) -> eyre::Result<()> {
+        move_spores(rpc, skeleton, self.out_points, self.lock, None).await
+    }
+}
+
+/// Move spore cells locked by the game contract back to `receiver`, their type and data are
+/// kept untouched
+///
+/// `since` is required unless the paired pve session is settled in the same transaction
+pub struct RedeemSporeCells {
+    pub out_points: Vec<(H256, u32)>,
+    pub receiver: Script,
+    pub since: Option<u64>,
+}
+
+#[async_trait]
+impl<T: RPC> Operation<T> for RedeemSporeCells {
+    async fn run(
+        self: Box<Self>,
+        rpc: &T,
+        skeleton: &mut TransactionSkeleton,
+        _log: &mut Log,
+    ) -> eyre::Result<()> {
+        move_spores(rpc, skeleton, self.out_points, self.receiver, self.since).await
+    }
+}
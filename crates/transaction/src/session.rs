@@ -0,0 +1,139 @@
+use ckb_cinnabar_calculator::{
+    instruction::Instruction,
+    operation::basic::{AddHeaderDepByInputIndex, AddInputCellByOutPoint, AddOutputCell},
+    re_exports::ckb_types::H256,
+    rpc::RPC,
+};
+use ckb_hash::blake2b_256;
+use common::{
+    cell::{GameGlobal, PveSession, PveSessionMaterials},
+    operation::{GameOperation, GameOperationSet},
+};
+use eyre::eyre;
+
+use crate::{
+    to_molecule, AddGameWitness, GameDeployment, IterateGameCell, LockupSporeCells,
+    RedeemSporeCells, SporeRedemption,
+};
+
+/// Session data committing to `materials`, which are revealed in the witness later on
+pub fn pve_session(
+    version: u8,
+    action_point: u16,
+    player_level: u8,
+    materials: &PveSessionMaterials,
+) -> eyre::Result<PveSession> {
+    Ok(PveSession {
+        version,
+        action_point,
+        player_level,
+        material_hash: blake2b_256(to_molecule(materials)?),
+    })
+}
+
+/// Create a pve session cell paired with the game global cell, the action points carried by
+/// the session are taken from the global cell
+///
+/// `spores` are the gameplay cards of the session, which are locked up by the spore lockup lock
+/// and redeemed once the session is settled
+pub fn create_pve_session<T: RPC>(
+    deployment: &GameDeployment,
+    global_out_point: (H256, u32),
+    mut global: GameGlobal,
+    session: PveSession,
+    spores: Vec<(H256, u32)>,
+) -> eyre::Result<Instruction<T>> {
+    global.action_point = global
+        .action_point
+        .checked_sub(session.action_point)
+        .ok_or_else(|| eyre!("insufficient action point"))?;
+    let (tx_hash, index) = global_out_point;
+    Ok(Instruction::new(vec![
+        deployment.cell_dep(),
+        Box::new(IterateGameCell {
+            tx_hash,
+            index,
+            data: to_molecule(&global)?,
+        }),
+        // Type ID makes the pve session cell uniquely typed
+        Box::new(AddOutputCell {
+            lock_script: deployment.pve_session_lock().into(),
+            type_script: None,
+            capacity: 0,
+            data: to_molecule(&session)?,
+            absolute_capacity: false,
+            type_id: true,
+        }),
+        Box::new(LockupSporeCells {
+            out_points: spores,
+            lock: deployment.spore_lockup_lock(),
+        }),
+    ]))
+}
+
+/// Iterate the pve session cell with the played operations, the material hash of `session`
+/// is refreshed from `materials`
+pub fn update_pve_session<T: RPC>(
+    deployment: &GameDeployment,
+    session_out_point: (H256, u32),
+    mut session: PveSession,
+    materials: &PveSessionMaterials,
+    operations: &[GameOperation],
+) -> eyre::Result<Instruction<T>> {
+    let materials_bin = to_molecule(materials)?;
+    session.material_hash = blake2b_256(&materials_bin);
+    let (tx_hash, index) = session_out_point;
+    Ok(Instruction::new(vec![
+        deployment.cell_dep(),
+        Box::new(IterateGameCell {
+            tx_hash,
+            index,
+            data: to_molecule(&session)?,
+        }),
+        Box::new(AddHeaderDepByInputIndex { input_index: 0 }),
+        Box::new(AddGameWitness {
+            materials: materials_bin,
+            operations: GameOperationSet::to_vec(operations.to_vec()),
+        }),
+    ]))
+}
+
+/// Burn the pve session cell to settle its result into the game global cell, and redeem
+/// the gameplay spores locked by the session
+///
+/// `materials` must be the ones committed by the material hash of the consumed session
+pub fn settle_pve_session<T: RPC>(
+    deployment: &GameDeployment,
+    session_out_point: (H256, u32),
+    materials: &PveSessionMaterials,
+    operations: &[GameOperation],
+    global_out_point: (H256, u32),
+    global: GameGlobal,
+    redemption: SporeRedemption,
+) -> eyre::Result<Instruction<T>> {
+    let (session_tx_hash, session_index) = session_out_point;
+    let (global_tx_hash, global_index) = global_out_point;
+    Ok(Instruction::new(vec![
+        deployment.cell_dep(),
+        Box::new(AddInputCellByOutPoint {
+            tx_hash: session_tx_hash,
+            index: session_index,
+            since: None,
+        }),
+        Box::new(AddHeaderDepByInputIndex { input_index: 0 }),
+        Box::new(AddGameWitness {
+            materials: to_molecule(materials)?,
+            operations: GameOperationSet::to_vec(operations.to_vec()),
+        }),
+        Box::new(IterateGameCell {
+            tx_hash: global_tx_hash,
+            index: global_index,
+            data: to_molecule(&global)?,
+        }),
+        Box::new(RedeemSporeCells {
+            out_points: redemption.spores,
+            receiver: redemption.receiver,
+            since: None,
+        }),
+    ]))
+}
@@ -0,0 +1,44 @@
+use ckb_cinnabar_calculator::{
+    instruction::Instruction,
+    re_exports::ckb_types::{packed::Script, H256},
+    rpc::RPC,
+};
+use common::{cell::GameGlobal, hardcoded::ABOUT_3_DAYS_BLOCKS};
+
+use crate::{to_molecule, GameDeployment, IterateGameCell, RedeemSporeCells};
+
+/// Relative since in block number, which the contract requires to redeem spores without
+/// settling their pve session
+pub const SPORE_REDEEM_SINCE: u64 = (1 << 63) | ABOUT_3_DAYS_BLOCKS;
+
+/// Spores locked by the game contract and the lock they are redeemed to
+pub struct SporeRedemption {
+    pub spores: Vec<(H256, u32)>,
+    pub receiver: Script,
+}
+
+/// Redeem spores locked by the game contract outside of settlement, which is allowed once they
+/// have been locked for the lockup period
+///
+/// The game global cell is iterated with `global` unchanged, to prove the ownership of spores
+pub fn redeem_spores<T: RPC>(
+    deployment: &GameDeployment,
+    global_out_point: (H256, u32),
+    global: GameGlobal,
+    redemption: SporeRedemption,
+) -> eyre::Result<Instruction<T>> {
+    let (tx_hash, index) = global_out_point;
+    Ok(Instruction::new(vec![
+        deployment.cell_dep(),
+        Box::new(IterateGameCell {
+            tx_hash,
+            index,
+            data: to_molecule(&global)?,
+        }),
+        Box::new(RedeemSporeCells {
+            out_points: redemption.spores,
+            receiver: redemption.receiver,
+            since: Some(SPORE_REDEEM_SINCE),
+        }),
+    ]))
+}
@@ -0,0 +1,525 @@
+use std::{env, fs, path::PathBuf};
+
+use ckb_cinnabar_calculator::{
+    instruction::{Instruction, TransactionCalculator},
+    re_exports::ckb_types::{
+        core::ScriptHashType,
+        packed::Script,
+        prelude::{Builder, Entity, Pack},
+        H256,
+    },
+    rpc::RpcClient,
+};
+use ckb_hash::blake2b_256;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use common::{
+    cell::{GameGlobal, PveSession, PveSessionMaterials},
+    hardcoded::{CARD_VERSION, DEFAULT_GAMEPLAY_CARDS, DNA, MAX_ACTION_POINT},
+    operation::{GameOperation, GameOperationSet},
+};
+use game_core::{card_pool, enemy_pool, player_pool, GameSetup, PveSystemRuntime};
+use serde::{de::DeserializeOwned, Serialize};
+use transaction::{
+    charge_action_point, create_pve_session, pve_session, redeem_spores, settle_pve_session,
+    update_pve_session, GameDeployment, SporeRedemption,
+};
+
+/// Game developer tools
+#[derive(Parser)]
+#[command(name = "runner game")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Replay operations from a seed, printing logs of each operation and final statistics
+    Simulate {
+        /// JSON file of the `GameOperation` array
+        #[arg(long)]
+        operations: PathBuf,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 1)]
+        player_level: u8,
+        #[arg(long, default_value_t = MAX_ACTION_POINT)]
+        action_point: u16,
+        #[arg(long, default_value_t = CARD_VERSION)]
+        version: u8,
+        /// Hex encoded gameplay card DNA, default gameplay cards are used if not provided
+        #[arg(long = "card")]
+        cards: Vec<String>,
+    },
+    /// Encode a JSON file into molecule bytes in hex
+    Encode {
+        #[arg(value_enum)]
+        layout: Layout,
+        /// JSON file to encode
+        json: PathBuf,
+    },
+    /// Decode hex molecule bytes into JSON
+    Decode {
+        #[arg(value_enum)]
+        layout: Layout,
+        /// Hex encoded molecule bytes, with or without 0x prefix
+        hex: String,
+    },
+    /// Compute `material_hash` of the hex encoded `PveSessionMaterials` molecule bytes
+    MaterialHash {
+        /// Hex encoded molecule bytes, with or without 0x prefix
+        hex: String,
+    },
+    /// Dump templates of content pool in JSON
+    DumpPool {
+        #[arg(value_enum)]
+        pool: Pool,
+    },
+    /// Build the transaction of a game contract flow and print it in hex, which is left
+    /// unbalanced and unsigned for the wallet
+    Transaction {
+        #[arg(long, value_enum, default_value_t = Network::Testnet)]
+        network: Network,
+        #[command(flatten)]
+        deployment: Deployment,
+        #[command(subcommand)]
+        flow: Flow,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Mainnet,
+    Testnet,
+}
+
+/// Where the game contract is deployed
+#[derive(Args)]
+struct Deployment {
+    /// Out point of the game contract cell in `<tx_hash>:<index>`
+    #[arg(long = "game-contract")]
+    contract: String,
+    /// Type hash of the game contract cell, which game scripts reference
+    #[arg(long = "game-type-hash")]
+    type_hash: String,
+}
+
+/// Out points are given in `<tx_hash>:<index>`, cell data and scripts in hex molecule bytes
+#[derive(Subcommand)]
+enum Flow {
+    /// Charge action points into the game global cell
+    ChargeActionPoint {
+        #[arg(long)]
+        global: String,
+        /// Data of the game global cell to charge
+        #[arg(long)]
+        global_data: String,
+        #[arg(long)]
+        amount: u16,
+    },
+    /// Create a pve session cell and lock its gameplay spores up
+    CreateSession {
+        #[arg(long)]
+        global: String,
+        /// Data of the game global cell which the action points are taken from
+        #[arg(long)]
+        global_data: String,
+        #[arg(long, default_value_t = CARD_VERSION)]
+        version: u8,
+        #[arg(long, default_value_t = MAX_ACTION_POINT)]
+        action_point: u16,
+        #[arg(long, default_value_t = 1)]
+        player_level: u8,
+        /// Materials committed by the session
+        #[arg(long)]
+        materials: String,
+        /// Gameplay spores of the session
+        #[arg(long = "spore")]
+        spores: Vec<String>,
+    },
+    /// Iterate a pve session cell with played operations
+    UpdateSession {
+        #[arg(long)]
+        session: String,
+        /// Data of the pve session cell to iterate
+        #[arg(long)]
+        session_data: String,
+        /// Materials to commit, which are revealed in the witness
+        #[arg(long)]
+        materials: String,
+        /// JSON file of the `GameOperation` array
+        #[arg(long)]
+        operations: PathBuf,
+    },
+    /// Burn a pve session cell into the game global cell and redeem its gameplay spores
+    SettleSession {
+        #[arg(long)]
+        session: String,
+        /// Materials committed by the session
+        #[arg(long)]
+        materials: String,
+        /// JSON file of the `GameOperation` array
+        #[arg(long)]
+        operations: PathBuf,
+        #[arg(long)]
+        global: String,
+        /// Data of the game global cell after settlement
+        #[arg(long)]
+        settled_global_data: String,
+        #[arg(long = "spore")]
+        spores: Vec<String>,
+        /// Lock script which the spores are redeemed to
+        #[arg(long)]
+        receiver: String,
+    },
+    /// Redeem spores locked by the game contract for the lockup period
+    RedeemSpores {
+        #[arg(long)]
+        global: String,
+        /// Data of the game global cell, which is kept unchanged
+        #[arg(long)]
+        global_data: String,
+        #[arg(long = "spore", required = true)]
+        spores: Vec<String>,
+        /// Lock script which the spores are redeemed to
+        #[arg(long)]
+        receiver: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layout {
+    Operations,
+    PveSession,
+    GameGlobal,
+    PveSessionMaterials,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Pool {
+    Card,
+    Enemy,
+    Player,
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    hex::decode(hex.trim().trim_start_matches("0x")).map_err(|error| error.to_string())
+}
+
+fn decode_h256(hex: &str) -> Result<H256, String> {
+    H256::from_slice(&decode_hex(hex)?).map_err(|error| error.to_string())
+}
+
+fn decode_out_point(out_point: &str) -> Result<(H256, u32), String> {
+    let (tx_hash, index) = out_point
+        .split_once(':')
+        .ok_or_else(|| format!("invalid out point: {out_point}"))?;
+    let index = index
+        .parse()
+        .map_err(|_| format!("invalid out point: {out_point}"))?;
+    Ok((decode_h256(tx_hash)?, index))
+}
+
+fn decode_out_points(out_points: &[String]) -> Result<Vec<(H256, u32)>, String> {
+    out_points
+        .iter()
+        .map(|out_point| decode_out_point(out_point))
+        .collect()
+}
+
+fn decode_script(hex: &str) -> Result<Script, String> {
+    Script::from_slice(&decode_hex(hex)?).map_err(|error| error.to_string())
+}
+
+fn decode_molecule<T: DeserializeOwned>(hex: &str) -> Result<T, String> {
+    serde_molecule::from_slice(&decode_hex(hex)?, false).map_err(|error| error.to_string())
+}
+
+fn read_operations(path: &PathBuf) -> Result<Vec<GameOperation>, String> {
+    let content = fs::read(path).map_err(|error| error.to_string())?;
+    serde_json::from_slice(&content).map_err(|error| error.to_string())
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|error| error.to_string())
+}
+
+fn json_to_molecule<T: Serialize + DeserializeOwned>(json: &[u8]) -> Result<Vec<u8>, String> {
+    let value: T = serde_json::from_slice(json).map_err(|error| error.to_string())?;
+    serde_molecule::to_vec(&value, false).map_err(|error| error.to_string())
+}
+
+fn molecule_to_json<T: Serialize + DeserializeOwned>(bin: &[u8]) -> Result<String, String> {
+    let value: T = serde_molecule::from_slice(bin, false).map_err(|error| error.to_string())?;
+    to_json(&value)
+}
+
+fn encode(layout: Layout, json: &[u8]) -> Result<Vec<u8>, String> {
+    match layout {
+        Layout::Operations => {
+            let operations: Vec<GameOperation> =
+                serde_json::from_slice(json).map_err(|error| error.to_string())?;
+            Ok(GameOperationSet::to_vec(operations))
+        }
+        Layout::PveSession => json_to_molecule::<PveSession>(json),
+        Layout::GameGlobal => json_to_molecule::<GameGlobal>(json),
+        Layout::PveSessionMaterials => json_to_molecule::<PveSessionMaterials>(json),
+    }
+}
+
+fn decode(layout: Layout, bin: &[u8]) -> Result<String, String> {
+    match layout {
+        Layout::Operations => {
+            let operation_set: GameOperationSet =
+                serde_molecule::from_slice(bin, false).map_err(|error| error.to_string())?;
+            to_json(&operation_set.operations)
+        }
+        Layout::PveSession => molecule_to_json::<PveSession>(bin),
+        Layout::GameGlobal => molecule_to_json::<GameGlobal>(bin),
+        Layout::PveSessionMaterials => molecule_to_json::<PveSessionMaterials>(bin),
+    }
+}
+
+fn material_hash(bin: &[u8]) -> Result<String, String> {
+    let _: PveSessionMaterials = serde_molecule::from_slice(bin, false)
+        .map_err(|error| format!("broken materials: {error}"))?;
+    Ok(hex::encode(blake2b_256(bin)))
+}
+
+fn dump_pool(pool: Pool) -> Result<String, String> {
+    let broken = || "pool bin in database is broken".to_string();
+    match pool {
+        Pool::Card => to_json(card_pool().ok_or_else(broken)?.cards()),
+        Pool::Enemy => to_json(enemy_pool().ok_or_else(broken)?.enemies()),
+        Pool::Player => to_json(player_pool().ok_or_else(broken)?.players()),
+    }
+}
+
+fn parse_cards(cards: &[String]) -> Result<Vec<DNA>, String> {
+    if cards.is_empty() {
+        return Ok(DEFAULT_GAMEPLAY_CARDS.to_vec());
+    }
+    cards
+        .iter()
+        .map(|card| {
+            decode_hex(card)?
+                .try_into()
+                .map_err(|_| format!("invalid card dna: {card}"))
+        })
+        .collect()
+}
+
+fn simulate(
+    operations: &[GameOperation],
+    seed: u64,
+    player_level: u8,
+    action_point: u16,
+    version: u8,
+    cards: Vec<DNA>,
+) -> Result<(), String> {
+    let mut game = PveSystemRuntime::new(seed).map_err(|error| error.to_string())?;
+    game.set_setup(GameSetup {
+        player_level,
+        action_point,
+        gameplay_cards: cards,
+        version,
+    });
+    for (i, operation) in operations.iter().enumerate() {
+        let result = game.apply_operation(operation);
+        println!(
+            "#{i} {}",
+            serde_json::to_string(operation).unwrap_or_default()
+        );
+        for log in game.logs() {
+            println!("  {}", serde_json::to_string(&log).unwrap_or_default());
+        }
+        if let Err(error) = result {
+            return Err(format!("operation #{i} failed: {error}"));
+        }
+        if game.game_over() {
+            println!("game over at operation #{i}");
+            break;
+        }
+    }
+    println!("gold: {}", game.get_gold());
+    println!("action point: {}", game.get_action_point());
+    println!("statistics: {}", to_json(game.statistics())?);
+    Ok(())
+}
+
+impl Deployment {
+    fn parse(&self) -> Result<GameDeployment, String> {
+        let (tx_hash, index) = decode_out_point(&self.contract)?;
+        let script = Script::new_builder()
+            .code_hash(decode_h256(&self.type_hash)?.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        Ok(GameDeployment {
+            tx_hash,
+            index,
+            script,
+        })
+    }
+}
+
+fn flow_instruction(
+    deployment: &GameDeployment,
+    flow: Flow,
+) -> Result<Instruction<RpcClient>, String> {
+    let instruction = match flow {
+        Flow::ChargeActionPoint {
+            global,
+            global_data,
+            amount,
+        } => charge_action_point(
+            deployment,
+            decode_out_point(&global)?,
+            decode_molecule(&global_data)?,
+            amount,
+        ),
+        Flow::CreateSession {
+            global,
+            global_data,
+            version,
+            action_point,
+            player_level,
+            materials,
+            spores,
+        } => {
+            let materials: PveSessionMaterials = decode_molecule(&materials)?;
+            let session = pve_session(version, action_point, player_level, &materials)
+                .map_err(|error| error.to_string())?;
+            create_pve_session(
+                deployment,
+                decode_out_point(&global)?,
+                decode_molecule(&global_data)?,
+                session,
+                decode_out_points(&spores)?,
+            )
+        }
+        Flow::UpdateSession {
+            session,
+            session_data,
+            materials,
+            operations,
+        } => update_pve_session(
+            deployment,
+            decode_out_point(&session)?,
+            decode_molecule(&session_data)?,
+            &decode_molecule(&materials)?,
+            &read_operations(&operations)?,
+        ),
+        Flow::SettleSession {
+            session,
+            materials,
+            operations,
+            global,
+            settled_global_data,
+            spores,
+            receiver,
+        } => settle_pve_session(
+            deployment,
+            decode_out_point(&session)?,
+            &decode_molecule(&materials)?,
+            &read_operations(&operations)?,
+            decode_out_point(&global)?,
+            decode_molecule(&settled_global_data)?,
+            SporeRedemption {
+                spores: decode_out_points(&spores)?,
+                receiver: decode_script(&receiver)?,
+            },
+        ),
+        Flow::RedeemSpores {
+            global,
+            global_data,
+            spores,
+            receiver,
+        } => redeem_spores(
+            deployment,
+            decode_out_point(&global)?,
+            decode_molecule(&global_data)?,
+            SporeRedemption {
+                spores: decode_out_points(&spores)?,
+                receiver: decode_script(&receiver)?,
+            },
+        ),
+    };
+    instruction.map_err(|error| error.to_string())
+}
+
+// Input cells are fetched from the node while the instruction is applied
+fn build_transaction(network: Network, deployment: Deployment, flow: Flow) -> Result<(), String> {
+    let instruction = flow_instruction(&deployment.parse()?, flow)?;
+    let rpc = match network {
+        Network::Mainnet => RpcClient::new_mainnet(),
+        Network::Testnet => RpcClient::new_testnet(),
+    };
+    let runtime = tokio::runtime::Runtime::new().map_err(|error| error.to_string())?;
+    let skeleton = runtime
+        .block_on(
+            TransactionCalculator::default()
+                .instruction(instruction)
+                .new_skeleton(&rpc),
+        )
+        .map_err(|error| error.to_string())?;
+    let tx = skeleton
+        .into_transaction_view()
+        .map_err(|error| error.to_string())?;
+    println!("0x{}", hex::encode(tx.data().as_slice()));
+    Ok(())
+}
+
+fn execute(command: Command) -> Result<(), String> {
+    match command {
+        Command::Simulate {
+            operations,
+            seed,
+            player_level,
+            action_point,
+            version,
+            cards,
+        } => {
+            let operations = read_operations(&operations)?;
+            let cards = parse_cards(&cards)?;
+            simulate(
+                &operations,
+                seed,
+                player_level,
+                action_point,
+                version,
+                cards,
+            )
+        }
+        Command::Encode { layout, json } => {
+            let content = fs::read(&json).map_err(|error| error.to_string())?;
+            println!("0x{}", hex::encode(encode(layout, &content)?));
+            Ok(())
+        }
+        Command::Decode { layout, hex } => {
+            println!("{}", decode(layout, &decode_hex(&hex)?)?);
+            Ok(())
+        }
+        Command::MaterialHash { hex } => {
+            println!("0x{}", material_hash(&decode_hex(&hex)?)?);
+            Ok(())
+        }
+        Command::DumpPool { pool } => {
+            println!("{}", dump_pool(pool)?);
+            Ok(())
+        }
+        Command::Transaction {
+            network,
+            deployment,
+            flow,
+        } => build_transaction(network, deployment, flow),
+    }
+}
+
+pub fn run() {
+    // Skip the binary name so that `game` is parsed as the command name
+    let cli = Cli::parse_from(env::args().skip(1));
+    if let Err(error) = execute(cli.command) {
+        eprintln!("game command failed: {error}");
+        std::process::exit(1);
+    }
+}
@@ -1,5 +1,14 @@
+use std::env;
+
 use ckb_cinnabar::dispatch;
 
+mod game;
+
 pub fn main() {
-    dispatch();
+    // Game developer commands live under `runner game`, the rest are served by cinnabar
+    if env::args().nth(1).as_deref() == Some("game") {
+        game::run();
+    } else {
+        dispatch();
+    }
 }
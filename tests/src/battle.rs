@@ -0,0 +1,45 @@
+use common::{
+    enemy::{EnemyLevel, EnemyName},
+    hardcoded::{DEFAULT_GAMEPLAY_CARDS, MAX_ACTION_POINT},
+    operation::GameOperation,
+};
+use game_core::Error;
+
+use crate::fixture::{start_game, BASE_SCENARIO, REBALANCED_SCENARIO};
+
+#[test]
+fn battles_spawn_enemies_of_session_version() {
+    for (version, hp) in [(BASE_SCENARIO, 20), (REBALANCED_SCENARIO, 30)] {
+        let mut game = start_game(version, DEFAULT_GAMEPLAY_CARDS.to_vec());
+        game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+            .expect("start battle");
+        let battle = game.view().battle.expect("battle running");
+        assert_eq!(battle.round, 1);
+        assert_eq!(battle.enemies.len(), 1);
+        assert_eq!(battle.enemies[0].enemy.name, EnemyName::Goblin);
+        assert_eq!(battle.enemies[0].hp, hp, "version {version}");
+    }
+}
+
+#[test]
+fn battles_charge_action_point_once() {
+    let mut game = start_game(BASE_SCENARIO, DEFAULT_GAMEPLAY_CARDS.to_vec());
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Normal))
+        .expect("start battle");
+    let normal_action_point = game
+        .view()
+        .player
+        .expect("player")
+        .player
+        .normal_action_point;
+    assert_eq!(
+        game.get_action_point(),
+        MAX_ACTION_POINT - normal_action_point as u16
+    );
+    let enemy = &game.view().battle.expect("battle running").enemies[0];
+    assert_eq!(enemy.enemy.name, EnemyName::Orc);
+    assert!((30..40).contains(&enemy.hp));
+
+    let result = game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy));
+    assert!(matches!(result, Err(Error::BattleAlreadyStarted)));
+}
@@ -0,0 +1,75 @@
+use ckb_cinnabar_calculator::{
+    re_exports::ckb_types::{packed, prelude::Entity as _, H256},
+    rpc::RpcClient,
+};
+use ckb_testtool::ckb_types::prelude::*;
+use common::{cell::GameGlobal, hardcoded::MAX_ACTION_POINT};
+use game::ScriptType;
+use transaction::{charge_action_point, create_pve_session, GameDeployment};
+
+use crate::*;
+
+// The deployed game contract is referenced by the script which the mocked chain builds
+pub(crate) fn deployment(chain: &mut GameChain) -> GameDeployment {
+    let script = chain.raw_game_script(vec![]);
+    GameDeployment {
+        tx_hash: H256::default(),
+        index: 0,
+        script: packed::Script::from_slice(script.as_slice()).expect("script"),
+    }
+}
+
+#[test]
+fn test_deployment_scripts_match_contract_layout() {
+    let mut chain = GameChain::default();
+    let deployment = deployment(&mut chain);
+
+    let global_type = chain.game_script(ScriptType::GameData, &[]);
+    assert_eq!(
+        deployment.game_global_type().as_slice(),
+        global_type.as_slice()
+    );
+    let session_lock = chain.game_script(
+        ScriptType::PveSession,
+        global_type.calc_script_hash().as_slice(),
+    );
+    assert_eq!(
+        deployment.pve_session_lock().as_slice(),
+        session_lock.as_slice()
+    );
+}
+
+#[test]
+fn test_session_material_hash_matches_contract() {
+    let (session, materials) = pve_session(1, 10, vec![0]);
+    assert_eq!(
+        session.material_hash,
+        ckb_testtool::ckb_hash::blake2b_256(to_molecule(&materials))
+    );
+}
+
+#[test]
+fn test_create_session_with_insufficient_action_point() {
+    let mut chain = GameChain::default();
+    let deployment = deployment(&mut chain);
+    let (session, _) = pve_session(1, 10, vec![0]);
+    let global = GameGlobal {
+        action_point: 9,
+        ..Default::default()
+    };
+    let result =
+        create_pve_session::<RpcClient>(&deployment, (H256::default(), 0), global, session, vec![]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_charge_action_point_overflow() {
+    let mut chain = GameChain::default();
+    let deployment = deployment(&mut chain);
+    let global = GameGlobal {
+        action_point: MAX_ACTION_POINT,
+        ..Default::default()
+    };
+    let result = charge_action_point::<RpcClient>(&deployment, (H256::default(), 0), global, 1);
+    assert!(result.is_err());
+}
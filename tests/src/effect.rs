@@ -0,0 +1,355 @@
+use common::{
+    card::{decode_dna, CardName},
+    effect::{EffectConfig, EffectName},
+    enemy::{roulette_action, Action, ActionConfig, EnemyLevel},
+    hardcoded::{DEFAULT_GAMEPLAY_CARDS, DNA},
+    operation::GameOperation,
+    value::ValueType,
+};
+use game_core::{Error, LogEvent, PveSystemRuntime, SignalLimits};
+
+use crate::fixture::{
+    card_pool, start_game, BASE_SCENARIO, DOUBLE_STRIKE_SCENARIO, EMPOWERED_SCENARIO,
+    MISPLACED_ENERGY_SCENARIO, MISPLACED_STUN_SCENARIO, OWNED_POISON_SCENARIO,
+    PLAYER_STRENGTH_SCENARIO, POISON_SCENARIO, SELF_STRENGTH_SCENARIO, THORNS_SCENARIO,
+};
+
+fn poison(countdown: ValueType) -> EffectConfig {
+    EffectConfig {
+        name: EffectName::Poison,
+        trap: false,
+        trigger: None,
+        owner_source: None,
+        owner_target: None,
+        value: Some(ValueType::Fixed(3)),
+        countdown: Some(countdown),
+    }
+}
+
+#[test]
+fn effect_actions_roll_into_effects() {
+    let seed = 0x0102_0304_0506_0708;
+    let Action::UseEffect(effect) =
+        roulette_action(&ActionConfig::Effect(poison(ValueType::Fixed(2))), seed)
+    else {
+        panic!("effect action not rolled into UseEffect");
+    };
+    assert!(effect.name == EffectName::Poison);
+    assert_eq!(effect.value, Some(3));
+    assert_eq!(effect.countdown, Some(2));
+
+    let Action::AddEnemeyEffect(effect) = roulette_action(
+        &ActionConfig::AddEffect(poison(ValueType::Random(1, 4))),
+        seed,
+    ) else {
+        panic!("add effect action not rolled into AddEnemeyEffect");
+    };
+    assert!((1..4).contains(&effect.countdown.unwrap()));
+}
+
+fn start_battle(scenario: u8) -> PveSystemRuntime {
+    let mut game = start_game(scenario, DEFAULT_GAMEPLAY_CARDS.to_vec());
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+        .expect("start battle");
+    game
+}
+
+fn round_over(game: &mut PveSystemRuntime, rounds: usize) {
+    (0..rounds).for_each(|_| {
+        game.apply_operation(&GameOperation::RoundOver)
+            .expect("round over")
+    });
+}
+
+#[test]
+fn enemies_act_at_their_turn() {
+    let mut game = start_battle(BASE_SCENARIO);
+    let battle = game.view().battle.expect("battle running");
+    assert!(matches!(
+        battle.enemies[0].active_actions[..],
+        [Action::UseAttack(5)]
+    ));
+
+    round_over(&mut game, 1);
+    let view = game.view();
+    assert_eq!(view.player.expect("player").hp, 55);
+    let battle = view.battle.expect("battle running");
+    assert_eq!(battle.round, 2);
+    assert_eq!(battle.enemies[0].active_actions.len(), 1);
+}
+
+#[test]
+fn poison_ticks_until_countdown_runs_out() {
+    let mut game = start_battle(POISON_SCENARIO);
+    round_over(&mut game, 1);
+    let player = game.view().player.expect("player");
+    assert_eq!(player.hp, 60);
+    let [poison] = &player.active_effects[..] else {
+        panic!("poison not added to the player");
+    };
+    assert!(poison.effect.name == EffectName::Poison);
+    assert_eq!(poison.countdown, 2);
+
+    round_over(&mut game, 3);
+    let player = game.view().player.expect("player");
+    assert_eq!(player.hp, 56);
+    assert!(player.active_effects.is_empty());
+}
+
+#[test]
+fn owner_filters_do_not_stop_ticks() {
+    let mut game = start_battle(OWNED_POISON_SCENARIO);
+    round_over(&mut game, 4);
+    let player = game.view().player.expect("player");
+    assert_eq!(player.hp, 56);
+    assert!(player.active_effects.is_empty());
+}
+
+#[test]
+fn enemy_effects_attach_to_enemy_itself() {
+    let mut game = start_battle(SELF_STRENGTH_SCENARIO);
+    round_over(&mut game, 1);
+    let view = game.view();
+    assert!(view.player.expect("player").active_effects.is_empty());
+    let battle = view.battle.expect("battle running");
+    let [strength] = &battle.enemies[0].active_effects[..] else {
+        panic!("strength not added to the enemy");
+    };
+    assert!(strength.effect.name == EffectName::Strength);
+    assert_eq!(strength.parent_runtime_id, battle.enemies[0].runtime_id);
+}
+
+#[test]
+fn player_death_ends_the_game() {
+    let mut game = start_battle(BASE_SCENARIO);
+    round_over(&mut game, 11);
+    assert_eq!(game.view().player.expect("player").hp, 5);
+    assert!(!game.game_over());
+
+    round_over(&mut game, 1);
+    assert!(game.game_over());
+    let view = game.view();
+    assert_eq!(view.player.expect("player").hp, 0);
+    assert!(view.battle.is_none());
+    let result = game.apply_operation(&GameOperation::RoundOver);
+    assert!(matches!(result, Err(Error::BattleNotStarted)));
+}
+
+#[test]
+fn default_cards_decode_into_card_names() {
+    let card_pool = card_pool();
+    let names = DEFAULT_GAMEPLAY_CARDS
+        .iter()
+        .map(|dna| decode_dna(&card_pool, dna, 0).expect("decode").name)
+        .collect::<Vec<_>>();
+    let attacks = names
+        .iter()
+        .filter(|name| **name == CardName::Attack)
+        .count();
+    assert_eq!((attacks, names.len() - attacks), (10, 10));
+    assert!(names[16] == CardName::Ambush);
+}
+
+// Ambush and 4 attacks, which are all drawn into the first hand
+fn ambush_deck() -> Vec<DNA> {
+    let mut deck = vec![DEFAULT_GAMEPLAY_CARDS[16]];
+    deck.extend([DEFAULT_GAMEPLAY_CARDS[0]; 4]);
+    deck
+}
+
+fn card_in_hand(game: &PveSystemRuntime, name: CardName) -> u16 {
+    game.view()
+        .player
+        .expect("player")
+        .handhold_cards
+        .iter()
+        .find(|card| card.card.name == name)
+        .expect("card in hand")
+        .runtime_id
+}
+
+#[test]
+fn attack_cards_damage_the_targeted_enemy() {
+    let mut game = start_game(BASE_SCENARIO, ambush_deck());
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+        .expect("start battle");
+    let attack = card_in_hand(&game, CardName::Attack);
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    game.apply_operation(&GameOperation::SpellCard(attack, Some(enemy)))
+        .expect("spell attack");
+
+    let view = game.view();
+    let player = view.player.expect("player");
+    assert_eq!(player.energy, 2);
+    assert_eq!(player.handhold_cards.len(), 4);
+    assert_eq!(player.grave_cards[0].runtime_id, attack);
+    assert_eq!(view.battle.expect("battle running").enemies[0].hp, 14);
+}
+
+#[test]
+fn traps_spring_exactly_once() {
+    let mut game = start_game(BASE_SCENARIO, ambush_deck());
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+        .expect("start battle");
+    let ambush = card_in_hand(&game, CardName::Ambush);
+    game.apply_operation(&GameOperation::SpellCard(ambush, None))
+        .expect("spell ambush");
+    let player = game.view().player.expect("player");
+    let [trap] = &player.active_effects[..] else {
+        panic!("trap not set to the player");
+    };
+    assert!(trap.effect.name == EffectName::Counterattack);
+    assert!(trap.effect.trap);
+
+    // the goblin attacks and the trap strikes back with the value of ambush
+    round_over(&mut game, 1);
+    let view = game.view();
+    let player = view.player.expect("player");
+    assert_eq!(player.hp, 55);
+    assert!(player.active_effects.is_empty());
+    assert_eq!(view.battle.expect("battle running").enemies[0].hp, 16);
+
+    round_over(&mut game, 1);
+    let view = game.view();
+    assert_eq!(view.player.expect("player").hp, 50);
+    assert_eq!(view.battle.expect("battle running").enemies[0].hp, 16);
+}
+
+#[test]
+fn owned_effects_only_power_up_the_owner() {
+    let mut game = start_battle(PLAYER_STRENGTH_SCENARIO);
+    let mut rounds: u16 = 0;
+    while game
+        .view()
+        .player
+        .expect("player")
+        .active_effects
+        .is_empty()
+    {
+        assert!(rounds < 8, "strength never cast onto the player");
+        round_over(&mut game, 1);
+        rounds += 1;
+    }
+    // the goblin attacks in every round but the one casting strength, and keeps its damage
+    // after that
+    round_over(&mut game, 1);
+    let player = game.view().player.expect("player");
+    assert_eq!(player.hp, 60 - 5 * rounds);
+    let [strength] = &player.active_effects[..] else {
+        panic!("strength not added to the player");
+    };
+    assert!(strength.effect.name == EffectName::Strength);
+
+    let attack = card_in_hand(&game, CardName::Attack);
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    game.apply_operation(&GameOperation::SpellCard(attack, Some(enemy)))
+        .expect("spell attack");
+    assert_eq!(
+        game.view().battle.expect("battle running").enemies[0].hp,
+        22
+    );
+}
+
+#[test]
+fn effects_of_invalid_parent_are_reported() {
+    let mut game = start_battle(MISPLACED_ENERGY_SCENARIO);
+    let result = game.apply_operation(&GameOperation::RoundOver);
+    assert!(matches!(
+        result,
+        Err(Error::EffectInvalidParent(EffectName::EnergyGain))
+    ));
+
+    // Rejected as soon as it is added to the player, rather than when it transforms anything
+    let mut game = start_battle(MISPLACED_STUN_SCENARIO);
+    let result = game.apply_operation(&GameOperation::RoundOver);
+    assert!(matches!(
+        result,
+        Err(Error::EffectInvalidParent(EffectName::Stun))
+    ));
+}
+
+// Targets and amounts of damage logged since the last call, in the order of resolution
+fn damages(game: &mut PveSystemRuntime) -> Vec<(u16, u16)> {
+    game.logs()
+        .into_iter()
+        .filter_map(|log| match log.event {
+            Some(LogEvent::Damage { target, amount, .. }) => Some((target, amount)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn sprung_traps_interrupt_queued_attacks() {
+    let mut game = start_game(DOUBLE_STRIKE_SCENARIO, ambush_deck());
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+        .expect("start battle");
+    let ambush = card_in_hand(&game, CardName::Ambush);
+    game.apply_operation(&GameOperation::SpellCard(ambush, None))
+        .expect("spell ambush");
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    game.logs();
+
+    // the counter strikes between both hits, which were queued together
+    round_over(&mut game, 1);
+    assert_eq!(damages(&mut game), vec![(3, 3), (enemy, 4), (3, 3)]);
+}
+
+#[test]
+fn before_hooks_resolve_ahead_of_the_signal() {
+    let mut game = start_battle(THORNS_SCENARIO);
+    round_over(&mut game, 1);
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    let attack = card_in_hand(&game, CardName::Attack);
+    game.logs();
+
+    // thorns prick the player before the attack lands on the goblin
+    game.apply_operation(&GameOperation::SpellCard(attack, Some(enemy)))
+        .expect("spell attack");
+    assert_eq!(damages(&mut game), vec![(3, 3), (enemy, 6)]);
+    let view = game.view();
+    assert_eq!(view.player.expect("player").hp, 57);
+    assert_eq!(view.battle.expect("battle running").enemies[0].hp, 24);
+}
+
+// Battle of the empowered scenario, whose two goblin actions have put both effects onto the player
+fn empowered_battle() -> (PveSystemRuntime, u16, u16) {
+    let mut game = start_battle(EMPOWERED_SCENARIO);
+    round_over(&mut game, 2);
+    let player = game.view().player.expect("player");
+    assert_eq!(player.active_effects.len(), 2);
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    let attack = card_in_hand(&game, CardName::Attack);
+    (game, enemy, attack)
+}
+
+#[test]
+fn transforms_stack_on_each_lineage_once() {
+    let (mut game, enemy, attack) = empowered_battle();
+    game.apply_operation(&GameOperation::SpellCard(attack, Some(enemy)))
+        .expect("spell attack");
+    assert_eq!(
+        game.view().battle.expect("battle running").enemies[0].hp,
+        30 - (6 + 2 + 3)
+    );
+}
+
+#[test]
+fn signal_limits_stop_operations_instead_of_hanging() {
+    let (mut game, enemy, attack) = empowered_battle();
+    game.set_signal_limits(SignalLimits {
+        max_transform_depth: 1,
+        ..Default::default()
+    });
+    let result = game.apply_operation(&GameOperation::SpellCard(attack, Some(enemy)));
+    assert!(matches!(result, Err(Error::SystemSignalLimitExceeded)));
+
+    // enemy turn, its attack and the turn of player are one more than allowed
+    let mut game = start_battle(BASE_SCENARIO);
+    game.set_signal_limits(SignalLimits {
+        max_signals_per_operation: 2,
+        ..Default::default()
+    });
+    let result = game.apply_operation(&GameOperation::RoundOver);
+    assert!(matches!(result, Err(Error::SystemSignalLimitExceeded)));
+}
@@ -0,0 +1,329 @@
+use common::{
+    card::{CardCategory, CardConfig, CardName, CardPool, CardTarget},
+    effect::{EffectConfig, EffectName},
+    enemy::{ActionConfig, EnemyConfig, EnemyLevel, EnemyName, EnemyPool, WeightedActionConfig},
+    hardcoded::{DNA, MAX_ACTION_POINT},
+    operation::GameOperation,
+    player::{PlayerConfig, PlayerPool},
+    value::ValueType::{self, Fixed, Random},
+};
+use game_core::{GameSetup, PveSystemRuntime};
+
+/// Player levels present in the fixture pool, other levels are left to cover the errors
+pub const PLAYER_LEVELS: [u8; 2] = [1, 2];
+
+// Content versions of the fixture pool, each of which sets up a scenario with the goblin of
+// that version, see `enemy_pool`
+pub const BASE_SCENARIO: u8 = 0;
+pub const REBALANCED_SCENARIO: u8 = 1;
+pub const POISON_SCENARIO: u8 = 2;
+pub const SELF_STRENGTH_SCENARIO: u8 = 3;
+pub const PLAYER_STRENGTH_SCENARIO: u8 = 4;
+pub const MISPLACED_ENERGY_SCENARIO: u8 = 5;
+pub const DOUBLE_STRIKE_SCENARIO: u8 = 6;
+pub const THORNS_SCENARIO: u8 = 7;
+pub const EMPOWERED_SCENARIO: u8 = 8;
+pub const MISPLACED_STUN_SCENARIO: u8 = 9;
+pub const OWNED_POISON_SCENARIO: u8 = 10;
+
+/// Install the fixture pools in place of the database bins, which are empty in this tree, so
+/// every test of the process plays the same content
+pub fn install_pools() {
+    game_core::install_pools(card_pool(), enemy_pool(), player_pool());
+    assert!(
+        game_core::player_pool().is_some(),
+        "pools have been looked up before fixtures installed"
+    );
+}
+
+/// Game of the first fixture player level started from a fixed seed, playing `gameplay_cards`
+/// against the content of `version`
+pub fn start_game(version: u8, gameplay_cards: Vec<DNA>) -> PveSystemRuntime {
+    install_pools();
+    let mut game = PveSystemRuntime::new(42).expect("new game");
+    game.set_setup(GameSetup {
+        player_level: PLAYER_LEVELS[0],
+        action_point: MAX_ACTION_POINT,
+        gameplay_cards,
+        version,
+    });
+    game.apply_operation(&GameOperation::StartGame)
+        .expect("start game");
+    game
+}
+
+pub fn card(
+    name: CardName,
+    rarity: u8,
+    category: CardCategory,
+    target: CardTarget,
+    value: u16,
+) -> CardConfig {
+    CardConfig {
+        version: 0,
+        name,
+        rarity,
+        exile: false,
+        cost: Fixed(1),
+        awake: None,
+        category,
+        target,
+        description: format!("{name} of fixture"),
+        value_0: Some(Fixed(value)),
+        value_1: None,
+    }
+}
+
+pub fn card_pool() -> CardPool {
+    CardPool::new(vec![
+        card(
+            CardName::Attack,
+            255,
+            CardCategory::Attack,
+            CardTarget::Enemy,
+            6,
+        ),
+        card(
+            CardName::Ambush,
+            120,
+            CardCategory::Trap,
+            CardTarget::Player,
+            4,
+        ),
+    ])
+}
+
+pub fn player_pool() -> PlayerPool {
+    PlayerPool::new(
+        PLAYER_LEVELS
+            .into_iter()
+            .map(|level| PlayerConfig {
+                level,
+                hp: Fixed(50 + level as u16 * 10),
+                energy: 3,
+                attack: Fixed(0),
+                defense: Fixed(0),
+                spirit: Fixed(0),
+                initial_handhold_capacity: 5,
+                initial_deck_capacity: 20,
+                initial_equipment_capacity: 0,
+                initial_sorcery_capacity: 0,
+                max_handhold_capacity: 10,
+                max_equipment_capacity: 0,
+                max_sorcery_capacity: 0,
+                heal_action_point: 10,
+                discard_action_point: 10,
+                easy_action_point: 10,
+                normal_action_point: 20,
+                hard_action_point: 30,
+            })
+            .collect(),
+    )
+}
+
+pub fn action(action: ActionConfig, amount: Option<u8>) -> WeightedActionConfig {
+    WeightedActionConfig {
+        action,
+        weight: 1,
+        amount,
+        tweakers: vec![],
+    }
+}
+
+pub fn enemy(
+    name: EnemyName,
+    level: EnemyLevel,
+    version: u8,
+    hp: ValueType,
+    actions: Vec<WeightedActionConfig>,
+) -> EnemyConfig {
+    EnemyConfig {
+        version,
+        name,
+        level,
+        hp,
+        gold: Fixed(10),
+        attack: Fixed(0),
+        defense: Fixed(0),
+        spirit: Fixed(0),
+        powerup_threshold: Fixed(0),
+        actions,
+    }
+}
+
+/// Effect of fixed value, which lasts the whole battle without `countdown`
+pub fn effect(name: EffectName, value: u16, countdown: Option<u16>) -> EffectConfig {
+    EffectConfig {
+        name,
+        trap: false,
+        trigger: None,
+        owner_source: None,
+        owner_target: None,
+        value: Some(Fixed(value)),
+        countdown: countdown.map(Fixed),
+    }
+}
+
+/// Goblins are the only easy enemies, each version of them plays one of the named scenarios
+pub fn enemy_pool() -> EnemyPool {
+    EnemyPool::new(vec![
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            BASE_SCENARIO,
+            Fixed(20),
+            vec![action(ActionConfig::Attack(Fixed(5)), None)],
+        ),
+        // rebalanced goblin of the next content version
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            REBALANCED_SCENARIO,
+            Fixed(30),
+            vec![action(ActionConfig::Attack(Fixed(5)), None)],
+        ),
+        // poisons the player once and then stays idle
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            POISON_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::Effect(effect(EffectName::Poison, 2, Some(2))),
+                Some(1),
+            )],
+        ),
+        // strengthens itself once and then stays idle
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            SELF_STRENGTH_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::AddEffect(EffectConfig {
+                    owner_source: Some(true),
+                    ..effect(EffectName::Strength, 2, None)
+                }),
+                Some(1),
+            )],
+        ),
+        // strengthens the player once, whose attacks rather than the goblin's are powered up
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            PLAYER_STRENGTH_SCENARIO,
+            Fixed(30),
+            vec![
+                action(
+                    ActionConfig::Effect(EffectConfig {
+                        owner_source: Some(true),
+                        ..effect(EffectName::Strength, 2, None)
+                    }),
+                    Some(1),
+                ),
+                action(ActionConfig::Attack(Fixed(5)), None),
+            ],
+        ),
+        // misconfigured to gain energy by itself, which only the player has
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            MISPLACED_ENERGY_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::AddEffect(effect(EffectName::EnergyGain, 1, None)),
+                Some(1),
+            )],
+        ),
+        // strikes twice in every turn
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            DOUBLE_STRIKE_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::MultipleAttack(Fixed(3), Fixed(2)),
+                None,
+            )],
+        ),
+        // grows thorns once and then stays idle
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            THORNS_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::AddEffect(effect(EffectName::Thorns, 3, None)),
+                Some(1),
+            )],
+        ),
+        // powers up the player twice, whose attacks are transformed by both effects
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            EMPOWERED_SCENARIO,
+            Fixed(30),
+            vec![
+                action(
+                    ActionConfig::Effect(EffectConfig {
+                        owner_source: Some(true),
+                        ..effect(EffectName::ExtraDamage, 2, None)
+                    }),
+                    Some(1),
+                ),
+                action(
+                    ActionConfig::Effect(EffectConfig {
+                        owner_source: Some(true),
+                        ..effect(EffectName::Strength, 3, None)
+                    }),
+                    Some(1),
+                ),
+            ],
+        ),
+        // misconfigured to stun the player, which only enemies can be
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            MISPLACED_STUN_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::Effect(effect(EffectName::Stun, 1, Some(1))),
+                Some(1),
+            )],
+        ),
+        // poisons the player once with an owner filter, which doesn't stop the ticks
+        enemy(
+            EnemyName::Goblin,
+            EnemyLevel::Easy,
+            OWNED_POISON_SCENARIO,
+            Fixed(30),
+            vec![action(
+                ActionConfig::Effect(EffectConfig {
+                    owner_source: Some(true),
+                    ..effect(EffectName::Poison, 2, Some(2))
+                }),
+                Some(1),
+            )],
+        ),
+        enemy(
+            EnemyName::Orc,
+            EnemyLevel::Normal,
+            0,
+            Random(30, 40),
+            vec![
+                action(ActionConfig::Attack(Random(6, 9)), None),
+                action(ActionConfig::Defense(Fixed(5)), None),
+            ],
+        ),
+        enemy(
+            EnemyName::Troll,
+            EnemyLevel::Hard,
+            0,
+            Fixed(60),
+            vec![
+                action(ActionConfig::MultipleAttack(Fixed(4), Fixed(2)), None),
+                action(ActionConfig::AddAttack(Fixed(1)), Some(2)),
+            ],
+        ),
+    ])
+}
@@ -0,0 +1,300 @@
+use std::{env, fs, path::PathBuf, str::FromStr};
+
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    ckb_error::Error,
+    ckb_types::{
+        bytes::Bytes,
+        core::{
+            HeaderBuilder, ScriptHashType, TransactionBuilder, TransactionView, TYPE_ID_CODE_HASH,
+        },
+        packed::{Byte32, CellInput, CellOutput, OutPoint, Script, WitnessArgs},
+        prelude::*,
+    },
+    context::Context,
+};
+use common::{
+    cell::{PveSession, PveSessionMaterials},
+    operation::{GameOperation, GameOperationSet},
+};
+use game::{ScriptError, ScriptType};
+
+#[cfg(test)]
+mod battle;
+#[cfg(test)]
+mod builder;
+#[cfg(test)]
+mod effect;
+#[cfg(test)]
+mod fixture;
+#[cfg(test)]
+mod log;
+// Only the native simulator recognizes the mocked spores which are locked up
+#[cfg(all(test, feature = "native-simulator"))]
+mod lockup;
+#[cfg(test)]
+mod pool;
+#[cfg(test)]
+mod replay;
+#[cfg(test)]
+mod tests;
+
+const TEST_ENV_VAR: &str = "CAPSULE_TEST_ENV";
+const MAX_CYCLES: u64 = 3_500_000_000;
+pub const CELL_CAPACITY: u64 = 1000 * 100_000_000;
+/// Nonce of the mocked block which game cells are linked to
+pub const GAME_SEED: u128 = 42;
+// Args of the type id script which the mocked spore code hash is derived from
+const MOCKED_SPORE_TYPE_ID: [u8; 32] = [0xff; 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestEnv {
+    Debug,
+    Release,
+}
+
+impl FromStr for TestEnv {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "debug" => Ok(TestEnv::Debug),
+            "release" => Ok(TestEnv::Release),
+            _ => Err("no match"),
+        }
+    }
+}
+
+/// Loader of the contract binaries built into `build/{debug,release}`
+pub struct Loader(PathBuf);
+
+impl Default for Loader {
+    fn default() -> Self {
+        let test_env = match env::var(TEST_ENV_VAR) {
+            Ok(val) => val.parse().expect("test env"),
+            Err(_) => TestEnv::Release,
+        };
+        Self::with_test_env(test_env)
+    }
+}
+
+impl Loader {
+    fn with_test_env(env: TestEnv) -> Self {
+        let load_prefix = match env {
+            TestEnv::Debug => "debug",
+            TestEnv::Release => "release",
+        };
+        let mut base_path = match env::var("TOP") {
+            Ok(val) => PathBuf::from(val),
+            Err(_) => PathBuf::from(".."),
+        };
+        base_path.push("build");
+        base_path.push(load_prefix);
+        Loader(base_path)
+    }
+
+    pub fn load_binary(&self, name: &str) -> Bytes {
+        let mut path = self.0.clone();
+        path.push(name);
+        fs::read(&path)
+            .unwrap_or_else(|_| panic!("Binary {:?} is missing!", path))
+            .into()
+    }
+}
+
+/// Mocked chain environment with the game contract and always-success script deployed
+pub struct GameChain {
+    pub context: Context,
+    pub always_success: Script,
+    spore_code_hash: Byte32,
+    game_out_point: OutPoint,
+}
+
+impl Default for GameChain {
+    fn default() -> Self {
+        // Native simulator of the game contract is picked up along with its binary if enabled
+        let mut context = Context::new_with_deployed_bins(vec![Loader::default().0]);
+        let game_out_point = context.deploy_cell_by_name("game");
+        let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+        let always_success = context
+            .build_script(&always_success_out_point, Bytes::new())
+            .expect("always success script");
+
+        // Spores are mocked by always-success deployed under the type id of mocked spore, which
+        // only the native simulator accepts
+        let spore_type_id = Script::new_builder()
+            .code_hash(TYPE_ID_CODE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(MOCKED_SPORE_TYPE_ID.to_vec().pack())
+            .build();
+        let spore_output = CellOutput::new_builder()
+            .capacity(CELL_CAPACITY.pack())
+            .type_(Some(spore_type_id.clone()).pack())
+            .build();
+        context.create_cell(spore_output, ALWAYS_SUCCESS.clone());
+        Self {
+            context,
+            always_success,
+            spore_code_hash: spore_type_id.calc_script_hash(),
+            game_out_point,
+        }
+    }
+}
+
+impl GameChain {
+    /// Game script whose args are the script type flag followed by `payload`
+    pub fn game_script(&mut self, script_type: ScriptType, payload: &[u8]) -> Script {
+        self.raw_game_script([&[script_type.into()], payload].concat())
+    }
+
+    pub fn raw_game_script(&mut self, args: Vec<u8>) -> Script {
+        self.context
+            .build_script(&self.game_out_point, Bytes::from(args))
+            .expect("game script")
+    }
+
+    /// Always-success script with distinct args, used to mock unique types
+    pub fn unique_script(&mut self, args: &[u8]) -> Script {
+        self.always_success
+            .clone()
+            .as_builder()
+            .args(Bytes::copy_from_slice(args).pack())
+            .build()
+    }
+
+    /// Spore type script with `args` as spore id, which passes any spore operation
+    pub fn spore_script(&self, args: &[u8]) -> Script {
+        Script::new_builder()
+            .code_hash(self.spore_code_hash.clone())
+            .hash_type(ScriptHashType::Type.into())
+            .args(Bytes::copy_from_slice(args).pack())
+            .build()
+    }
+
+    pub fn cell_output(&self, lock: Option<Script>, type_: Option<Script>) -> CellOutput {
+        CellOutput::new_builder()
+            .capacity(CELL_CAPACITY.pack())
+            .lock(lock.unwrap_or_else(|| self.always_success.clone()))
+            .type_(type_.pack())
+            .build()
+    }
+
+    pub fn create_input(&mut self, output: CellOutput, data: Vec<u8>) -> CellInput {
+        let out_point = self.context.create_cell(output, Bytes::from(data));
+        CellInput::new_builder().previous_output(out_point).build()
+    }
+
+    /// Link the input cell to a mocked block, whose nonce is used as game seed
+    pub fn link_header(&mut self, input: &CellInput, nonce: u128) -> Byte32 {
+        let header = HeaderBuilder::default().nonce(nonce.pack()).build();
+        self.context.insert_header(header.clone());
+        self.context
+            .link_cell_with_block(input.previous_output(), header.hash(), 0);
+        header.hash()
+    }
+
+    /// Transaction which starts from the cell of `output`, it is placed at the first input so
+    /// that its witness is the first one, and is linked to the mocked block if `with_header`
+    pub fn start_tx(
+        &mut self,
+        output: CellOutput,
+        data: Vec<u8>,
+        with_header: bool,
+    ) -> TransactionBuilder {
+        let input = self.create_input(output, data);
+        let tx = TransactionBuilder::default().input(input.clone());
+        if with_header {
+            let header_hash = self.link_header(&input, GAME_SEED);
+            tx.header_dep(header_hash)
+        } else {
+            tx
+        }
+    }
+
+    /// Consume and recreate the cell of `output` in `tx`, with the data unchanged
+    pub fn iterate_cell(
+        &mut self,
+        tx: TransactionBuilder,
+        output: CellOutput,
+        data: Vec<u8>,
+    ) -> TransactionBuilder {
+        let input = self.create_input(output.clone(), data.clone());
+        push_output(tx.input(input), output, data)
+    }
+
+    pub fn complete(&mut self, tx: TransactionBuilder) -> TransactionView {
+        self.context.complete_tx(tx.build())
+    }
+
+    pub fn verify(&self, tx: &TransactionView) -> Result<u64, Error> {
+        self.context.verify_tx(tx, MAX_CYCLES)
+    }
+}
+
+/// Session data and its witness materials, the material hash is filled accordingly
+pub fn pve_session(
+    player_level: u8,
+    action_point: u16,
+    archive_output: Vec<u8>,
+) -> (PveSession, PveSessionMaterials) {
+    let materials = PveSessionMaterials {
+        dna_collection: vec![],
+        archive_input: vec![],
+        archive_output,
+    };
+    let session =
+        transaction::pve_session(0, action_point, player_level, &materials).expect("pve session");
+    (session, materials)
+}
+
+pub fn push_output(
+    tx: TransactionBuilder,
+    output: CellOutput,
+    data: Vec<u8>,
+) -> TransactionBuilder {
+    tx.output(output).output_data(Bytes::from(data).pack())
+}
+
+/// Transaction mocked by a test case, whose fields are tweaked from a passing default
+pub trait TxCase {
+    fn build(self, chain: &mut GameChain) -> TransactionView;
+}
+
+pub fn assert_case_pass(case: impl TxCase) {
+    let mut chain = GameChain::default();
+    let tx = case.build(&mut chain);
+    chain.verify(&tx).expect("pass verification");
+}
+
+pub fn assert_case_error(case: impl TxCase, error: ScriptError) {
+    let mut chain = GameChain::default();
+    let tx = case.build(&mut chain);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, error as i8);
+}
+
+pub fn to_molecule<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    serde_molecule::to_vec(value, false).expect("molecule")
+}
+
+pub fn session_witness(input_type: Option<Vec<u8>>, output_type: Option<Vec<u8>>) -> Bytes {
+    WitnessArgs::new_builder()
+        .input_type(input_type.map(Bytes::from).pack())
+        .output_type(output_type.map(Bytes::from).pack())
+        .build()
+        .as_bytes()
+}
+
+pub fn operations_bytes(operations: &[GameOperation]) -> Vec<u8> {
+    GameOperationSet::to_vec(operations.to_vec())
+}
+
+pub fn assert_script_error(err: Error, err_code: i8) {
+    let error_string = err.to_string();
+    assert!(
+        error_string.contains(format!("error code {} ", err_code).as_str()),
+        "error_string: {}, expected_error_code: {}",
+        error_string,
+        err_code
+    );
+}
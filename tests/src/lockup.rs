@@ -0,0 +1,159 @@
+use ckb_cinnabar_calculator::re_exports::ckb_types::{packed, prelude::Entity as _};
+use ckb_testtool::ckb_types::prelude::*;
+use common::cell::GameGlobal;
+use game::ScriptError;
+use transaction::SPORE_REDEEM_SINCE;
+
+use crate::{builder::deployment, *};
+
+// Scripts built by the deployment are converted into the ckb types of the mocked chain
+fn deployed_script(script: packed::Script) -> Script {
+    Script::from_slice(script.as_slice()).expect("script")
+}
+
+/// Transaction which iterates the game global cell, if `global_iterated`, and moves `spores` out
+/// of their cells
+fn move_spores_tx(
+    chain: &mut GameChain,
+    spores: Vec<(CellInput, CellOutput)>,
+    global_iterated: bool,
+) -> TransactionBuilder {
+    let deployment = deployment(chain);
+    let plain_output = chain.cell_output(None, None);
+    let mut tx = chain.start_tx(plain_output.clone(), vec![], false);
+    tx = push_output(tx, plain_output, vec![]);
+    if global_iterated {
+        let global_type = deployed_script(deployment.game_global_type());
+        let global_output = chain.cell_output(None, Some(global_type));
+        tx = chain.iterate_cell(tx, global_output, to_molecule(&GameGlobal::default()));
+    }
+    for (input, output) in spores {
+        tx = push_output(tx.input(input), output, vec![]);
+    }
+    tx
+}
+
+fn locked_spore(chain: &mut GameChain, since: u64) -> (CellInput, Script) {
+    let lockup_lock = deployed_script(deployment(chain).spore_lockup_lock());
+    let spore_type = chain.spore_script(b"locked spore");
+    let output = chain.cell_output(Some(lockup_lock), Some(spore_type.clone()));
+    let input = chain.create_input(output, vec![]);
+    (input.as_builder().since(since.pack()).build(), spore_type)
+}
+
+#[test]
+fn test_spore_lockup_create_settle_redeem() {
+    let mut chain = GameChain::default();
+    let deployment = deployment(&mut chain);
+    let session_lock = deployed_script(deployment.pve_session_lock());
+    let lockup_lock = deployed_script(deployment.spore_lockup_lock());
+    let owner_lock = chain.always_success.clone();
+    let (session, _) = pve_session(1, 10, vec![0]);
+    let session_data = to_molecule(&session);
+
+    // Session creation locks spores of the owner up
+    let spore_types = [b"spore 0", b"spore 1"].map(|id| chain.spore_script(id));
+    let spores = spore_types
+        .iter()
+        .map(|spore_type| {
+            let output = chain.cell_output(None, Some(spore_type.clone()));
+            let input = chain.create_input(output, vec![]);
+            let locked = chain.cell_output(Some(lockup_lock.clone()), Some(spore_type.clone()));
+            (input, locked)
+        })
+        .collect();
+    let session_type = chain.unique_script(b"pve session");
+    let session_output = chain.cell_output(Some(session_lock), Some(session_type));
+    let tx = move_spores_tx(&mut chain, spores, true);
+    let tx = push_output(tx, session_output.clone(), session_data.clone());
+    let create_tx = chain.complete(tx);
+    chain.verify(&create_tx).expect("create session");
+
+    // Settlement redeems the first locked spore right away
+    let locked_outputs = create_tx
+        .outputs()
+        .into_iter()
+        .filter(|output| output.lock().as_slice() == lockup_lock.as_slice())
+        .collect::<Vec<_>>();
+    assert_eq!(locked_outputs.len(), spore_types.len());
+    let locked_input = chain.create_input(locked_outputs[0].clone(), vec![]);
+    let redeemed = chain.cell_output(Some(owner_lock.clone()), locked_outputs[0].type_().to_opt());
+    let tx = chain.start_tx(session_output, session_data, true);
+    let global_type = deployed_script(deployment.game_global_type());
+    let global_output = chain.cell_output(None, Some(global_type));
+    let tx = chain.iterate_cell(tx, global_output, to_molecule(&GameGlobal::default()));
+    let tx = push_output(tx.input(locked_input), redeemed, vec![]);
+    let settle_tx = chain.complete(tx);
+    chain.verify(&settle_tx).expect("settle session");
+
+    // The other one is redeemed after the lockup period
+    let locked_input = chain.create_input(locked_outputs[1].clone(), vec![]);
+    let locked_input = locked_input
+        .as_builder()
+        .since(SPORE_REDEEM_SINCE.pack())
+        .build();
+    let redeemed = chain.cell_output(Some(owner_lock), locked_outputs[1].type_().to_opt());
+    let tx = move_spores_tx(&mut chain, vec![(locked_input, redeemed)], true);
+    let redeem_tx = chain.complete(tx);
+    chain.verify(&redeem_tx).expect("redeem spores");
+}
+
+#[test]
+fn test_spore_lockup_not_spore() {
+    let mut chain = GameChain::default();
+    let deployment = deployment(&mut chain);
+    let session_lock = deployed_script(deployment.pve_session_lock());
+    let lockup_lock = deployed_script(deployment.spore_lockup_lock());
+    let plain_type = chain.unique_script(b"not spore");
+    let output = chain.cell_output(None, Some(plain_type.clone()));
+    let input = chain.create_input(output, vec![]);
+    let locked = chain.cell_output(Some(lockup_lock), Some(plain_type));
+    let session_type = chain.unique_script(b"pve session");
+    let session_output = chain.cell_output(Some(session_lock), Some(session_type));
+    let tx = move_spores_tx(&mut chain, vec![(input, locked)], true);
+    let (session, _) = pve_session(1, 10, vec![0]);
+    let tx = chain.complete(push_output(tx, session_output, to_molecule(&session)));
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadSporeLockupMode as i8);
+}
+
+#[test]
+fn test_spore_redeem_period_not_enough() {
+    let mut chain = GameChain::default();
+    let (input, spore_type) = locked_spore(&mut chain, SPORE_REDEEM_SINCE - 1);
+    let redeemed = chain.cell_output(None, Some(spore_type));
+    let tx = move_spores_tx(&mut chain, vec![(input, redeemed)], true);
+    let tx = chain.complete(tx);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::RedeemPeriodNotEnough as i8);
+
+    // Absolute since never counts the lockup period
+    let (input, spore_type) = locked_spore(&mut chain, SPORE_REDEEM_SINCE & !(1 << 63));
+    let redeemed = chain.cell_output(None, Some(spore_type));
+    let tx = move_spores_tx(&mut chain, vec![(input, redeemed)], true);
+    let tx = chain.complete(tx);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::RedeemPeriodNotEnough as i8);
+}
+
+#[test]
+fn test_spore_redeem_bad_mode() {
+    let mut chain = GameChain::default();
+    let (input, spore_type) = locked_spore(&mut chain, SPORE_REDEEM_SINCE);
+    let redeemed = chain.cell_output(None, Some(spore_type));
+    let tx = move_spores_tx(&mut chain, vec![(input, redeemed)], false);
+    let tx = chain.complete(tx);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadRedeemMode as i8);
+}
+
+#[test]
+fn test_spore_cannot_be_burned() {
+    let mut chain = GameChain::default();
+    let (input, _) = locked_spore(&mut chain, SPORE_REDEEM_SINCE);
+    let burned = chain.cell_output(None, None);
+    let tx = move_spores_tx(&mut chain, vec![(input, burned)], true);
+    let tx = chain.complete(tx);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::SporeCannotBeBurned as i8);
+}
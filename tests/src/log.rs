@@ -0,0 +1,91 @@
+use common::{enemy::EnemyLevel, hardcoded::DEFAULT_GAMEPLAY_CARDS, operation::GameOperation};
+use game_core::{CardPile, Log, LogEvent, LogName};
+
+use crate::fixture::{start_game, BASE_SCENARIO};
+
+#[test]
+fn legacy_log_still_deserializes() {
+    let legacy = r#"{"name":"SelectCards","attribute":"{\"source\":\"Deck\",\"source_runtime_id\":1,\"count\":1}","value":1,"recipient":3}"#;
+    let log: Log = serde_json::from_str(legacy).expect("legacy log");
+    assert!(matches!(log.name, LogName::SelectCards));
+    assert!(log.event.is_none());
+    assert_eq!(log.recipient, Some(3));
+    assert_eq!(log.operation, 0);
+    assert!(log.cause.is_empty());
+}
+
+#[test]
+fn log_event_round_trip() {
+    let json = r#"{"name":"CardMove","value":null,"recipient":3,"event":{"CardMove":{"card":9,"from":"Deck","to":"Hand"}},"operation":0}"#;
+    let log: Log = serde_json::from_str(json).expect("event log");
+    assert!(matches!(
+        log.event,
+        Some(LogEvent::CardMove {
+            card: 9,
+            from: CardPile::Deck,
+            to: CardPile::Hand,
+        })
+    ));
+    assert_eq!(serde_json::to_string(&log).expect("serialize log"), json);
+}
+
+#[test]
+fn log_cause_round_trip() {
+    let json = r#"{"name":"Damage","value":6,"recipient":5,"operation":2,"cause":[{"name":"SpellCard","source_runtime_id":7},{"name":"ChangeHp","source_runtime_id":7,"transformed_by":9}]}"#;
+    let log: Log = serde_json::from_str(json).expect("traced log");
+    assert_eq!(log.operation, 2);
+    assert_eq!(log.cause.len(), 2);
+    assert_eq!(log.cause[1].transformed_by, Some(9));
+    assert_eq!(serde_json::to_string(&log).expect("serialize log"), json);
+}
+
+#[test]
+fn runtimes_log_card_moves_and_damage() {
+    let mut game = start_game(BASE_SCENARIO, DEFAULT_GAMEPLAY_CARDS.to_vec());
+    game.logs();
+    game.apply_operation(&GameOperation::StartBattle(EnemyLevel::Easy))
+        .expect("start battle");
+    let drawn = game
+        .logs()
+        .into_iter()
+        .filter(|log| {
+            matches!(
+                log.event,
+                Some(LogEvent::CardMove {
+                    from: CardPile::Deck,
+                    to: CardPile::Hand,
+                    ..
+                })
+            )
+        })
+        .count();
+    assert_eq!(drawn, 5);
+
+    let enemy = game.view().battle.expect("battle running").enemies[0].runtime_id;
+    game.apply_operation(&GameOperation::RoundOver)
+        .expect("round over");
+    let logs = game.logs();
+    assert!(logs.iter().any(|log| matches!(
+        log.event,
+        Some(LogEvent::Damage {
+            source,
+            target: 3,
+            amount: 5,
+            blocked: 0,
+        }) if source == enemy
+    )));
+    let discarded = logs
+        .iter()
+        .filter(|log| {
+            matches!(
+                log.event,
+                Some(LogEvent::CardMove {
+                    from: CardPile::Hand,
+                    to: CardPile::Grave,
+                    ..
+                })
+            )
+        })
+        .count();
+    assert_eq!(discarded, 5);
+}
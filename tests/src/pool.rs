@@ -0,0 +1,75 @@
+use common::{
+    card::{roll_card_dna, CardCategory, CardName, CardPool, CardTarget, Rarity},
+    hardcoded::DNA_RARITY_INDEX,
+    player::PlayerPool,
+};
+
+use crate::fixture::{card, card_pool, player_pool, PLAYER_LEVELS};
+
+#[test]
+fn duplicate_cards_resolve_to_the_first() {
+    let cards = ["first", "second"].map(|description| {
+        let mut template = card(
+            CardName::Attack,
+            255,
+            CardCategory::Attack,
+            CardTarget::Enemy,
+            6,
+        );
+        template.description = description.to_string();
+        template
+    });
+    let mut newer = cards[0].clone();
+    newer.version = 1;
+    newer.description = "newer".to_string();
+    let pool = CardPool::new([cards.to_vec(), vec![newer]].concat());
+
+    let template = pool.card_by_name(CardName::Attack, 0).expect("card");
+    assert_eq!(template.description, "first");
+    let template = pool.card_by_name(CardName::Attack, 3).expect("card");
+    assert_eq!(template.description, "newer");
+    let resolved = pool
+        .versioned_cards(0)
+        .map(|card| card.description.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(resolved, ["first"]);
+}
+
+#[test]
+fn duplicate_levels_resolve_to_the_first() {
+    let players = player_pool().players().to_vec();
+    let mut duplicate = players[0].clone();
+    duplicate.initial_handhold_capacity += 1;
+    let pool = PlayerPool::new([players.clone(), vec![duplicate]].concat());
+
+    let template = pool.player_by_level(PLAYER_LEVELS[0]).expect("player");
+    assert_eq!(
+        template.initial_handhold_capacity,
+        players[0].initial_handhold_capacity
+    );
+}
+
+#[test]
+fn rarity_rolls_keep_drop_rates_of_all_tiers() {
+    assert!(
+        (0..=u16::MAX).all(|seed| Rarity::roll_among(seed, |_| true) == Some(Rarity::roll(seed)))
+    );
+    assert!(Rarity::roll_among(0, |_| false).is_none());
+}
+
+#[test]
+fn missing_tiers_share_their_drops_in_proportion() {
+    // the fixture pool only has a rare and a common card, whose rates are 250 and 650
+    let pool = card_pool();
+    let rolls = 9000;
+    let rares = (0..rolls)
+        .map(|seed| roll_card_dna(&pool, seed).expect("card dna"))
+        .filter(|dna| Rarity::from(dna[DNA_RARITY_INDEX]) == Rarity::Rare)
+        .count();
+    assert!(
+        (2250..2750).contains(&rares),
+        "{rares} rare cards out of {rolls}"
+    );
+
+    assert!(roll_card_dna(&CardPool::new(vec![]), 0).is_none());
+}
@@ -0,0 +1,253 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use common::{
+    hardcoded::{CARD_VERSION, DEFAULT_GAMEPLAY_CARDS, MAX_ACTION_POINT},
+    operation::GameOperation,
+};
+use game_core::{Error, GameSetup, PveSystemRuntime};
+use proptest::{collection, option, prelude::*};
+
+use crate::fixture::{install_pools, PLAYER_LEVELS};
+
+#[derive(Debug, Clone)]
+struct Session {
+    seed: u64,
+    player_level: u8,
+    action_point: u16,
+}
+
+/// Outcome of each operation, including its error and the logs it emitted
+type Trace = Vec<String>;
+
+// Levels outside of the fixture pool are generated as well, whose games fail to start
+fn session_strategy() -> impl Strategy<Value = Session> {
+    (any::<u64>(), 0u8..4, 0..=MAX_ACTION_POINT).prop_map(|(seed, player_level, action_point)| {
+        Session {
+            seed,
+            player_level,
+            action_point,
+        }
+    })
+}
+
+// Runtime ids beyond the generated cards are allowed to cover invalid operations as well
+fn operation_strategy() -> impl Strategy<Value = GameOperation> {
+    let runtime_id = 0u16..40;
+    prop_oneof![
+        1 => Just(GameOperation::StartGame),
+        2 => (0u8..3).prop_map(|level| GameOperation::StartBattle(level.into())),
+        3 => Just(GameOperation::RoundOver),
+        6 => (runtime_id.clone(), option::of(runtime_id.clone()))
+            .prop_map(|(card, target)| GameOperation::SpellCard(card, target)),
+        2 => collection::vec(runtime_id, 0..4).prop_map(GameOperation::SelectCard),
+        1 => Just(GameOperation::HealHp),
+        1 => Just(GameOperation::DestroyCard),
+    ]
+}
+
+fn setup(session: &Session) -> GameSetup {
+    GameSetup {
+        player_level: session.player_level,
+        action_point: session.action_point,
+        gameplay_cards: DEFAULT_GAMEPLAY_CARDS.to_vec(),
+        version: CARD_VERSION,
+    }
+}
+
+// Replay stops at the first error the same as the contract does, which is reported by the flag
+fn replay(game: &mut PveSystemRuntime, operations: &[GameOperation]) -> (Trace, bool) {
+    let mut trace = vec![];
+    for operation in operations {
+        let result = game.apply_operation(operation);
+        let failed = result.is_err();
+        trace.push(format!("{operation:?} => {result:?}, {:?}", game.logs()));
+        if failed {
+            return (trace, false);
+        }
+    }
+    (trace, true)
+}
+
+fn archive(game: &PveSystemRuntime) -> Vec<u8> {
+    serde_molecule::to_vec(game, false).expect("archive game")
+}
+
+fn fingerprint(game: &PveSystemRuntime) -> (Vec<u8>, bool, u16, u16) {
+    (
+        archive(game),
+        game.game_over(),
+        game.get_gold(),
+        game.get_action_point(),
+    )
+}
+
+fn view(game: &PveSystemRuntime) -> String {
+    serde_json::to_string(&game.view()).expect("game view")
+}
+
+fn run_session(session: &Session, operations: &[GameOperation]) -> (Trace, bool, PveSystemRuntime) {
+    install_pools();
+    let mut game = PveSystemRuntime::new(session.seed).expect("new game");
+    game.set_setup(setup(session));
+    let (trace, completed) = replay(&mut game, operations);
+    (trace, completed, game)
+}
+
+#[test]
+fn fixture_levels_start_games() {
+    for player_level in 0..4 {
+        let session = Session {
+            seed: 42,
+            player_level,
+            action_point: MAX_ACTION_POINT,
+        };
+        let (trace, completed, game) = run_session(&session, &[GameOperation::StartGame]);
+        assert_eq!(
+            completed,
+            PLAYER_LEVELS.contains(&player_level),
+            "{trace:?}"
+        );
+        assert!(!game.game_over());
+    }
+}
+
+#[test]
+fn unknown_level_fails_to_start() {
+    install_pools();
+    let mut game = PveSystemRuntime::new(42).expect("new game");
+    game.set_setup(setup(&Session {
+        seed: 42,
+        player_level: 0,
+        action_point: MAX_ACTION_POINT,
+    }));
+    let result = game.apply_operation(&GameOperation::StartGame);
+    assert!(matches!(result, Err(Error::PlayerLevelNotFound(0))));
+}
+
+// Replay `operations` in the same way as the contract does
+fn contract_replay(
+    session: &Session,
+    operations: &[GameOperation],
+) -> Result<PveSystemRuntime, Error> {
+    let setup = setup(session);
+    PveSystemRuntime::replay(
+        session.seed,
+        setup.player_level,
+        setup.action_point,
+        setup.gameplay_cards,
+        setup.version,
+        operations,
+    )
+}
+
+#[test]
+fn rejected_operation_is_not_recorded() {
+    let session = Session {
+        seed: 42,
+        player_level: PLAYER_LEVELS[0],
+        action_point: MAX_ACTION_POINT,
+    };
+    let (_, completed, mut game) = run_session(&session, &[GameOperation::StartGame]);
+    assert!(completed);
+    let result = game.apply_operation(&GameOperation::RoundOver);
+    assert!(matches!(result, Err(Error::BattleNotStarted)));
+    game.apply_operation(&GameOperation::StartBattle(0.into()))
+        .expect("start battle");
+    assert_eq!(game.operations().len(), 2);
+
+    let replayed = contract_replay(&session, game.operations()).expect("replay operations");
+    assert_eq!(fingerprint(&replayed), fingerprint(&game));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn replay_never_panics(
+        session in session_strategy(),
+        operations in collection::vec(operation_strategy(), 0..64),
+    ) {
+        let result = catch_unwind(AssertUnwindSafe(|| run_session(&session, &operations)));
+        prop_assert!(result.is_ok(), "operations panicked: {:?}", operations);
+    }
+
+    #[test]
+    fn replay_is_deterministic(
+        session in session_strategy(),
+        operations in collection::vec(operation_strategy(), 0..64),
+    ) {
+        let (first_trace, _, first_game) = run_session(&session, &operations);
+        let (second_trace, _, second_game) = run_session(&session, &operations);
+        prop_assert_eq!(first_trace, second_trace);
+        prop_assert_eq!(fingerprint(&first_game), fingerprint(&second_game));
+    }
+
+    #[test]
+    fn recorded_operations_pass_replay(
+        session in session_strategy(),
+        operations in collection::vec(operation_strategy(), 0..64),
+    ) {
+        // Clients keep playing after rejected operations, which are left out of the record
+        install_pools();
+        let mut game = PveSystemRuntime::new(session.seed).expect("new game");
+        game.set_setup(setup(&session));
+        for operation in &operations {
+            let _ = game.apply_operation(operation);
+        }
+        let replayed = contract_replay(&session, game.operations());
+        prop_assert!(replayed.is_ok(), "replay failed: {:?}", game.operations());
+        prop_assert_eq!(fingerprint(&replayed.unwrap()), fingerprint(&game));
+    }
+
+    #[test]
+    fn archive_round_trip_keeps_replay(
+        session in session_strategy(),
+        operations in collection::vec(operation_strategy(), 1..64),
+        split in any::<prop::sample::Index>(),
+    ) {
+        let split = split.index(operations.len());
+        let (prefix, suffix) = operations.split_at(split);
+        let (expected_trace, _, expected_game) = run_session(&session, &operations);
+
+        let (mut trace, completed, game) = run_session(&session, prefix);
+        let archive_bin = archive(&game);
+        let mut restored = PveSystemRuntime::load(&archive_bin).expect("restore game");
+        prop_assert_eq!(&archive(&restored), &archive_bin);
+
+        // Only archives taken outside of battles and selections can resume the game
+        prop_assume!(completed && game.archivable());
+        restored.set_setup(setup(&session));
+        trace.extend(replay(&mut restored, suffix).0);
+        prop_assert_eq!(trace, expected_trace);
+        prop_assert_eq!(fingerprint(&restored), fingerprint(&expected_game));
+    }
+
+    #[test]
+    fn undo_redo_matches_replay(
+        session in session_strategy(),
+        operations in collection::vec(operation_strategy(), 1..64),
+        undo in any::<prop::sample::Index>(),
+    ) {
+        let (_, _, mut game) = run_session(&session, &operations);
+        let recorded = game.operations().clone();
+        prop_assume!(!recorded.is_empty());
+        let (expected_archive, expected_view) = (fingerprint(&game), view(&game));
+        let undo = undo.index(recorded.len()) + 1;
+        for _ in 0..undo {
+            prop_assert!(game.undo().expect("undo"));
+        }
+        let kept = recorded.len() - undo;
+        let (_, _, shorter) = run_session(&session, &recorded[..kept]);
+        prop_assert_eq!(game.operations().len(), kept);
+        prop_assert_eq!(fingerprint(&game), fingerprint(&shorter));
+        prop_assert_eq!(view(&game), view(&shorter));
+
+        // Only succeeded operations are recorded, so all of them are redone
+        while game.can_redo() {
+            prop_assert!(game.redo().expect("redo"));
+        }
+        prop_assert_eq!(game.operations().len(), recorded.len());
+        prop_assert_eq!(fingerprint(&game), expected_archive);
+        prop_assert_eq!(view(&game), expected_view);
+    }
+}
@@ -0,0 +1,670 @@
+use ckb_testtool::ckb_types::{
+    bytes::Bytes,
+    core::{ScriptHashType, TransactionView},
+    packed,
+    prelude::*,
+};
+use common::{
+    card::merge_cards_dna,
+    cell::GameGlobal,
+    contract::{DobGlobalStatistics, Script, SporeData},
+    hardcoded::{
+        DNA, DNA_CARD_INDEX, DNA_GOLDEN_INDEX, DNA_LEN, DNA_RARITY_INDEX, MERGE_CARD_COUNT,
+        MOCKED_SPORE_CODE_HASH, SPORE_CODE_HASH_SET,
+    },
+};
+use game::{ScriptError, ScriptType};
+
+use crate::*;
+
+fn game_global_data() -> Vec<u8> {
+    to_molecule(&GameGlobal::default())
+}
+
+// GameData and TokenIssuer cells share the same creation/transfer/burn patterns
+fn typed_cell_tx(
+    chain: &mut GameChain,
+    script_type: ScriptType,
+    inputs: usize,
+    outputs: usize,
+) -> TransactionView {
+    let type_ = chain.game_script(script_type, &[]);
+    let typed_output = chain.cell_output(None, Some(type_));
+    let plain_output = chain.cell_output(None, None);
+    let mut tx = chain.start_tx(plain_output.clone(), vec![], false);
+    tx = push_output(tx, plain_output, vec![]);
+    for _ in 0..inputs {
+        let input = chain.create_input(typed_output.clone(), game_global_data());
+        tx = tx.input(input);
+    }
+    for _ in 0..outputs {
+        tx = push_output(tx, typed_output.clone(), game_global_data());
+    }
+    chain.complete(tx)
+}
+
+#[test]
+fn test_game_data_create_transfer_burn() {
+    let mut chain = GameChain::default();
+    for (inputs, outputs) in [(0, 1), (1, 1), (1, 0)] {
+        let tx = typed_cell_tx(&mut chain, ScriptType::GameData, inputs, outputs);
+        chain.verify(&tx).expect("pass verification");
+    }
+}
+
+#[test]
+fn test_game_data_bad_init_mode() {
+    let mut chain = GameChain::default();
+    let tx = typed_cell_tx(&mut chain, ScriptType::GameData, 0, 2);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadGameGlobalInitMode as i8);
+}
+
+#[test]
+fn test_token_issuer_create_transfer_burn() {
+    let mut chain = GameChain::default();
+    for (inputs, outputs) in [(0, 1), (1, 1), (1, 0)] {
+        let tx = typed_cell_tx(&mut chain, ScriptType::TokenIssuer, inputs, outputs);
+        chain.verify(&tx).expect("pass verification");
+    }
+}
+
+#[test]
+fn test_token_issuer_bad_issue_mode() {
+    let mut chain = GameChain::default();
+    let tx = typed_cell_tx(&mut chain, ScriptType::TokenIssuer, 2, 1);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadTokenIssueMode as i8);
+}
+
+fn raw_args_tx(chain: &mut GameChain, args: Vec<u8>) -> TransactionView {
+    let type_ = chain.raw_game_script(args);
+    let plain_output = chain.cell_output(None, None);
+    let tx = chain.start_tx(plain_output, vec![], false);
+    let tx = push_output(tx, chain.cell_output(None, Some(type_)), vec![]);
+    chain.complete(tx)
+}
+
+#[test]
+fn test_empty_script_args() {
+    let mut chain = GameChain::default();
+    let tx = raw_args_tx(&mut chain, vec![]);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::ScriptArgsUnexpected as i8);
+}
+
+#[test]
+fn test_unknown_script_type() {
+    let mut chain = GameChain::default();
+    let tx = raw_args_tx(&mut chain, vec![0xff]);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::UnknownScriptType as i8);
+}
+
+/// Pve session transaction, which is an update by default
+struct PveSessionCase {
+    session_data: Vec<u8>,
+    witness: Bytes,
+    typed: bool,
+    with_header: bool,
+    session_outputs: usize,
+    global_iterated: bool,
+}
+
+impl Default for PveSessionCase {
+    fn default() -> Self {
+        let (session, materials) = pve_session(1, 10, vec![0]);
+        Self {
+            session_data: to_molecule(&session),
+            witness: session_witness(Some(to_molecule(&materials)), Some(operations_bytes(&[]))),
+            typed: true,
+            with_header: true,
+            session_outputs: 1,
+            global_iterated: false,
+        }
+    }
+}
+
+impl PveSessionCase {
+    fn settlement() -> Self {
+        Self {
+            session_outputs: 0,
+            global_iterated: true,
+            ..Default::default()
+        }
+    }
+
+    fn with_materials(materials: Vec<u8>, operations: Vec<u8>) -> Self {
+        let (mut session, _) = pve_session(1, 10, vec![0]);
+        session.material_hash = ckb_testtool::ckb_hash::blake2b_256(&materials);
+        Self {
+            session_data: to_molecule(&session),
+            witness: session_witness(Some(materials), Some(operations)),
+            ..Default::default()
+        }
+    }
+}
+
+impl TxCase for PveSessionCase {
+    fn build(self, chain: &mut GameChain) -> TransactionView {
+        let global_type = chain.game_script(ScriptType::GameData, &[]);
+        let global_type_hash = global_type.calc_script_hash();
+        let session_lock = chain.game_script(ScriptType::PveSession, global_type_hash.as_slice());
+        let session_type = self.typed.then(|| chain.unique_script(b"pve session"));
+        let session_output = chain.cell_output(Some(session_lock), session_type);
+
+        let mut tx = chain
+            .start_tx(
+                session_output.clone(),
+                self.session_data.clone(),
+                self.with_header,
+            )
+            .witness(self.witness.pack());
+        for _ in 0..self.session_outputs {
+            tx = push_output(tx, session_output.clone(), self.session_data.clone());
+        }
+        if self.global_iterated {
+            let global_output = chain.cell_output(None, Some(global_type));
+            tx = chain.iterate_cell(tx, global_output, game_global_data());
+        }
+        chain.complete(tx)
+    }
+}
+
+#[test]
+fn test_pve_session_update() {
+    assert_case_pass(PveSessionCase::default());
+}
+
+#[test]
+fn test_pve_session_settlement() {
+    assert_case_pass(PveSessionCase::settlement());
+}
+
+#[test]
+fn test_pve_session_bad_update_mode() {
+    let case = PveSessionCase {
+        global_iterated: true,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::BadPveUpdateMode);
+
+    // Update without archiving the game is not allowed
+    let (_, materials) = pve_session(1, 10, vec![]);
+    let case = PveSessionCase::with_materials(to_molecule(&materials), operations_bytes(&[]));
+    assert_case_error(case, ScriptError::BadPveUpdateMode);
+}
+
+#[test]
+fn test_pve_session_bad_settlement_mode() {
+    let case = PveSessionCase {
+        global_iterated: false,
+        ..PveSessionCase::settlement()
+    };
+    assert_case_error(case, ScriptError::BadPveSettlementMode);
+}
+
+#[test]
+fn test_pve_session_must_be_typed() {
+    let case = PveSessionCase {
+        typed: false,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::PveSessionMustBeTyped);
+}
+
+#[test]
+fn test_pve_session_broken_molecule() {
+    let case = PveSessionCase {
+        session_data: vec![1, 2, 3],
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::BrokenPveSessionMolecule);
+}
+
+#[test]
+fn test_pve_session_header_not_set() {
+    let case = PveSessionCase {
+        with_header: false,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::HeaderNotSet);
+}
+
+#[test]
+fn test_pve_session_witness_not_set() {
+    let (_, materials) = pve_session(1, 10, vec![0]);
+    let case = PveSessionCase {
+        witness: session_witness(Some(to_molecule(&materials)), None),
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::WitnessOutputTypeNotSet);
+
+    let case = PveSessionCase {
+        witness: session_witness(None, Some(operations_bytes(&[]))),
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::WitnessInputTypeNotSet);
+}
+
+#[test]
+fn test_pve_session_material_hash_mismatch() {
+    let (_, materials) = pve_session(2, 10, vec![1]);
+    let case = PveSessionCase {
+        witness: session_witness(Some(to_molecule(&materials)), Some(operations_bytes(&[]))),
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::MaterialHashMismatch);
+}
+
+#[test]
+fn test_pve_session_broken_witness() {
+    let (_, materials) = pve_session(1, 10, vec![0]);
+    let case = PveSessionCase::with_materials(to_molecule(&materials), vec![1, 2, 3]);
+    assert_case_error(case, ScriptError::BrokenOperationsBytes);
+
+    let case = PveSessionCase::with_materials(vec![1, 2, 3], operations_bytes(&[]));
+    assert_case_error(case, ScriptError::BrokenPveSessionMaterialsMolecule);
+}
+
+// Pvp session cell is locked by the game script, which pairs with two game global cells
+fn pvp_session_tx(chain: &mut GameChain, xudt_typed: bool, both_iterated: bool) -> TransactionView {
+    let global_types = [
+        chain.game_script(ScriptType::GameData, &[1]),
+        chain.game_script(ScriptType::GameData, &[2]),
+    ];
+    let payload = global_types
+        .iter()
+        .flat_map(|type_| type_.calc_script_hash().raw_data().to_vec())
+        .collect::<Vec<_>>();
+    let session_lock = chain.game_script(ScriptType::PvpSession, &payload);
+    let session_type = xudt_typed.then(|| chain.unique_script(b"xudt"));
+    let session_output = chain.cell_output(Some(session_lock), session_type);
+    let mut tx = chain.start_tx(session_output, 100u128.to_le_bytes().to_vec(), false);
+    let iterated = if both_iterated { 2 } else { 1 };
+    for global_type in global_types.into_iter().take(iterated) {
+        let global_output = chain.cell_output(None, Some(global_type));
+        tx = chain.iterate_cell(tx, global_output, game_global_data());
+    }
+    chain.complete(tx)
+}
+
+#[test]
+fn test_pvp_session_bad_settlement_mode() {
+    let mut chain = GameChain::default();
+    let tx = pvp_session_tx(&mut chain, true, false);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadPvpSettlementMode as i8);
+}
+
+#[test]
+fn test_pvp_session_not_xudt_typed() {
+    // Mocked xUDT type doesn't match the hardcoded xUDT code hash
+    let mut chain = GameChain::default();
+    for xudt_typed in [false, true] {
+        let tx = pvp_session_tx(&mut chain, xudt_typed, true);
+        let err = chain.verify(&tx).unwrap_err();
+        assert_script_error(err, ScriptError::PvpSessionNotXudtTyped as i8);
+    }
+}
+
+const BLINDBOX_CLUSTER_ID: [u8; 32] = [1; 32];
+const CARD_CLUSTER_ID: [u8; 32] = [2; 32];
+const UNBOX_CKB_BASE: u64 = 100;
+
+fn dob_global() -> DobGlobalStatistics {
+    DobGlobalStatistics {
+        protocol_payee: Script {
+            code_hash: [3; 32],
+            hash_type: ScriptHashType::Type as u8,
+            args: vec![],
+        },
+        blindbox_cluster_id: BLINDBOX_CLUSTER_ID,
+        card_cluster_id: CARD_CLUSTER_ID,
+        ckb_base: UNBOX_CKB_BASE,
+        ..Default::default()
+    }
+}
+
+/// Spore data of the cluster, whose content is the DNA in hex
+fn spore_data(cluster_id: [u8; 32], dna: &[u8]) -> Vec<u8> {
+    let content = dna
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    to_molecule(&SporeData {
+        content_type: b"dob/1".to_vec(),
+        content: content.into_bytes(),
+        cluster_id: Some(cluster_id.to_vec()),
+    })
+}
+
+// Same as how the contract derives seeds of merging, from the headers of `commitments` spores
+fn derive_seed(material: &[u8], commitments: usize) -> u64 {
+    let nonces = (GAME_SEED as u64).to_le_bytes().repeat(commitments);
+    let seed_material = [material, &nonces[..]].concat();
+    let seed_hash = ckb_testtool::ckb_hash::blake2b_256(seed_material);
+    u64::from_le_bytes(seed_hash[..8].try_into().unwrap())
+}
+
+fn card_dna(card: u8, rarity: u8) -> DNA {
+    let mut dna = [0u8; DNA_LEN];
+    dna[DNA_RARITY_INDEX] = rarity;
+    dna[DNA_CARD_INDEX] = card;
+    dna
+}
+
+fn merged_dna(dna_set: &[DNA]) -> DNA {
+    let seed = derive_seed(&dna_set.concat(), dna_set.len());
+    merge_cards_dna(dna_set, seed).expect("mergeable cards")
+}
+
+fn spore_id(source: &str, index: usize) -> Vec<u8> {
+    format!("{source} spore {index}").into_bytes()
+}
+
+/// Dob commitment lock paired with the dob global, revealing spores to the `receiver` lock
+fn dob_commitment_lock(chain: &mut GameChain, receiver: &packed::Script) -> packed::Script {
+    let dob_global_lock = chain.game_script(ScriptType::DobGlobal, &[]);
+    let payload = [
+        dob_global_lock.calc_script_hash().as_slice(),
+        receiver.calc_script_hash().as_slice(),
+    ]
+    .concat();
+    chain.game_script(ScriptType::DobCommitment, &payload)
+}
+
+/// Dob global transaction, which merges cards of common rarity by default
+///
+/// Input spores are committed to the always-success receiver, and linked to the committing
+/// block if `with_header`
+struct DobGlobalCase {
+    input_data: Vec<u8>,
+    output_data: Option<Vec<u8>>,
+    with_header: bool,
+    committed: bool,
+    receiver: Option<packed::Script>,
+    input_spores: Vec<Vec<u8>>,
+    output_spores: Vec<Vec<u8>>,
+    payment: Option<u64>,
+}
+
+impl Default for DobGlobalCase {
+    fn default() -> Self {
+        let data = to_molecule(&dob_global());
+        let dna_set = [card_dna(0, u8::MAX); MERGE_CARD_COUNT];
+        Self {
+            input_data: data.clone(),
+            output_data: Some(data),
+            with_header: true,
+            committed: true,
+            receiver: None,
+            input_spores: dna_set
+                .iter()
+                .map(|dna| spore_data(CARD_CLUSTER_ID, dna))
+                .collect(),
+            output_spores: vec![spore_data(CARD_CLUSTER_ID, &merged_dna(&dna_set))],
+            payment: None,
+        }
+    }
+}
+
+impl DobGlobalCase {
+    /// Unboxing of the first blindbox, paid at the base price
+    ///
+    /// The card DNA is only checked against the generated card pool of `database`, which this
+    /// tree doesn't carry, so unboxing is only covered up to the payment check
+    #[cfg(feature = "native-simulator")]
+    fn unbox() -> Self {
+        let mut dob_global = dob_global();
+        let input_data = to_molecule(&dob_global);
+        dob_global.global_unboxed_count += 1;
+        Self {
+            input_data,
+            output_data: Some(to_molecule(&dob_global)),
+            input_spores: vec![spore_data(BLINDBOX_CLUSTER_ID, &[])],
+            output_spores: vec![spore_data(CARD_CLUSTER_ID, &card_dna(0, u8::MAX))],
+            payment: Some(UNBOX_CKB_BASE * common::hardcoded::CKB_DECIMAL),
+            ..Default::default()
+        }
+    }
+}
+
+impl TxCase for DobGlobalCase {
+    fn build(self, chain: &mut GameChain) -> TransactionView {
+        let dob_global_lock = chain.game_script(ScriptType::DobGlobal, &[]);
+        let dob_global_output = chain.cell_output(Some(dob_global_lock), None);
+        let mut tx = chain.start_tx(dob_global_output.clone(), self.input_data, false);
+        if let Some(output_data) = self.output_data {
+            tx = push_output(tx, dob_global_output, output_data);
+        }
+
+        let receiver = self
+            .receiver
+            .unwrap_or_else(|| chain.always_success.clone());
+        let input_lock = self
+            .committed
+            .then(|| dob_commitment_lock(chain, &receiver));
+        for (i, data) in self.input_spores.into_iter().enumerate() {
+            let spore_type = chain.spore_script(&spore_id("input", i));
+            let spore_output = chain.cell_output(input_lock.clone(), Some(spore_type));
+            let input = chain.create_input(spore_output, data);
+            if self.with_header {
+                let header_hash = chain.link_header(&input, GAME_SEED);
+                tx = tx.header_dep(header_hash);
+            }
+            tx = tx.input(input);
+        }
+        for (i, data) in self.output_spores.into_iter().enumerate() {
+            let spore_type = chain.spore_script(&spore_id("output", i));
+            tx = push_output(tx, chain.cell_output(None, Some(spore_type)), data);
+        }
+        if let Some(payment) = self.payment {
+            let payee = dob_global().protocol_payee;
+            let payee_lock = packed::Script::new_builder()
+                .code_hash(payee.code_hash.pack())
+                .hash_type(packed::Byte::new(payee.hash_type))
+                .args(payee.args.pack())
+                .build();
+            let payee_output = chain
+                .cell_output(Some(payee_lock), None)
+                .as_builder()
+                .capacity(payment.pack())
+                .build();
+            tx = push_output(tx, payee_output, vec![]);
+        }
+        chain.complete(tx)
+    }
+}
+
+#[test]
+fn test_mocked_spore_code_hash() {
+    let chain = GameChain::default();
+    let code_hash = chain.spore_script(&[]).code_hash();
+    assert_eq!(code_hash.as_slice(), MOCKED_SPORE_CODE_HASH);
+    assert!(!SPORE_CODE_HASH_SET
+        .iter()
+        .any(|hash| hash.as_slice() == code_hash.as_slice()));
+}
+
+#[test]
+fn test_dob_global_bad_mode() {
+    let case = DobGlobalCase {
+        output_data: None,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::BadDobGlobalMode);
+}
+
+#[test]
+fn test_dob_global_broken_molecule() {
+    let case = DobGlobalCase {
+        input_data: vec![1, 2, 3],
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::BrokenDobGlobalMolecule);
+}
+
+#[test]
+fn test_dob_global_unexpected_changed() {
+    let mut dob_global = dob_global();
+    dob_global.global_unboxed_count = 1;
+    let case = DobGlobalCase {
+        output_data: Some(to_molecule(&dob_global)),
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::DobGlobalUnexpectedChanged);
+}
+
+// Spores are only recognized by the native simulator, which accepts the mocked spore
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_spore_cards_merge() {
+    assert_case_pass(DobGlobalCase::default());
+
+    // Legendary cards are merged into the golden one
+    let dna_set = [card_dna(0, 0); MERGE_CARD_COUNT];
+    let case = DobGlobalCase {
+        input_spores: dna_set
+            .iter()
+            .map(|dna| spore_data(CARD_CLUSTER_ID, dna))
+            .collect(),
+        output_spores: vec![spore_data(CARD_CLUSTER_ID, &merged_dna(&dna_set))],
+        ..Default::default()
+    };
+    assert_case_pass(case);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_dob_global_header_not_set() {
+    let case = DobGlobalCase {
+        with_header: false,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::HeaderNotSet);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_dob_spore_not_committed() {
+    let case = DobGlobalCase {
+        committed: false,
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::DobSporeNotCommitted);
+
+    let case = DobGlobalCase {
+        committed: false,
+        ..DobGlobalCase::unbox()
+    };
+    assert_case_error(case, ScriptError::DobSporeNotCommitted);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_dob_receiver_mismatch() {
+    let mut chain = GameChain::default();
+    let case = DobGlobalCase {
+        receiver: Some(chain.unique_script(b"receiver")),
+        ..Default::default()
+    };
+    let tx = case.build(&mut chain);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::DobReceiverMismatch as i8);
+}
+
+#[test]
+fn test_dob_commitment_bad_mode() {
+    let mut chain = GameChain::default();
+    let receiver = chain.always_success.clone();
+    let commitment_lock = dob_commitment_lock(&mut chain, &receiver);
+    let spore_type = chain.spore_script(&spore_id("input", 0));
+    let committed_output = chain.cell_output(Some(commitment_lock), Some(spore_type));
+    let tx = chain.start_tx(committed_output.clone(), vec![], false);
+    let tx = chain.complete(push_output(tx, committed_output, vec![]));
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::BadDobCommitmentMode as i8);
+
+    // Commitment records both the dob global and the receiver
+    let commitment_lock = chain.game_script(ScriptType::DobCommitment, &[0; 32]);
+    let committed_output = chain.cell_output(Some(commitment_lock), None);
+    let tx = chain.start_tx(committed_output, vec![], false);
+    let tx = chain.complete(tx);
+    let err = chain.verify(&tx).unwrap_err();
+    assert_script_error(err, ScriptError::ScriptArgsUnexpected as i8);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_spore_cards_merge_count_mismatch() {
+    let mut case = DobGlobalCase::default();
+    case.input_spores.pop();
+    assert_case_error(case, ScriptError::CardMergeCountMismatch);
+
+    let mut case = DobGlobalCase::default();
+    case.output_spores.clear();
+    assert_case_error(case, ScriptError::CardMergeCountMismatch);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_spore_cards_merge_not_allowed() {
+    let mut case = DobGlobalCase::default();
+    case.input_spores[0] = spore_data(CARD_CLUSTER_ID, &card_dna(1, u8::MAX));
+    assert_case_error(case, ScriptError::CardMergeNotAllowed);
+
+    // Golden legendary cards are at the top already
+    let mut golden = card_dna(0, 0);
+    golden[DNA_GOLDEN_INDEX] = 1;
+    let mut case = DobGlobalCase::default();
+    case.input_spores = vec![spore_data(CARD_CLUSTER_ID, &golden); MERGE_CARD_COUNT];
+    assert_case_error(case, ScriptError::CardMergeNotAllowed);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_spore_cards_merge_dna_mismatch() {
+    let case = DobGlobalCase {
+        output_spores: vec![spore_data(CARD_CLUSTER_ID, &card_dna(0, 0))],
+        ..Default::default()
+    };
+    assert_case_error(case, ScriptError::CardMergeDnaMismatch);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_gameplay_spore_dna_unexpected() {
+    let mut case = DobGlobalCase::default();
+    case.input_spores[0] = spore_data(CARD_CLUSTER_ID, &[0; DNA_LEN - 1]);
+    assert_case_error(case, ScriptError::GameplaySporeDnaUnexpected);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_blindbox_unbox_count_mismatch() {
+    let mut case = DobGlobalCase::unbox();
+    case.output_spores
+        .push(spore_data(BLINDBOX_CLUSTER_ID, &[]));
+    assert_case_error(case, ScriptError::UnboxCountMismatch);
+
+    let mut case = DobGlobalCase::unbox();
+    case.output_spores.clear();
+    assert_case_error(case, ScriptError::UnboxCountMismatch);
+}
+
+#[cfg(feature = "native-simulator")]
+#[test]
+fn test_blindbox_unbox_payment_not_enough() {
+    let case = DobGlobalCase {
+        payment: None,
+        ..DobGlobalCase::unbox()
+    };
+    assert_case_error(case, ScriptError::UnboxPaymentNotEnough);
+
+    let case = DobGlobalCase {
+        payment: Some(UNBOX_CKB_BASE * common::hardcoded::CKB_DECIMAL - 1),
+        ..DobGlobalCase::unbox()
+    };
+    assert_case_error(case, ScriptError::UnboxPaymentNotEnough);
+}